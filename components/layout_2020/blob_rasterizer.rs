@@ -1,31 +1,277 @@
 use std::collections::HashMap;
 use std::num::NonZeroUsize;
 use std::sync::{Arc, Mutex};
-use std::mem::ManuallyDrop;
 
-use euclid::point2;
 use rayon::iter::IntoParallelIterator;
 use rayon::prelude::*;
 use rayon::{ThreadPool, ThreadPoolBuilder};
+use serde::{Deserialize, Serialize};
 use tiny_skia::{Mask, Paint, Pixmap, Rect, FillRule, Transform, PathBuilder};
+use ttf_parser::{Face, GlyphId, OutlineBuilder};
 use webrender_api::units::{BlobDirtyRect, BlobToDeviceTranslation, DeviceIntRect, LayoutPoint, LayoutRect};
 use webrender_api::{
     AsyncBlobImageRasterizer, BlobImageData, BlobImageHandler, BlobImageKey, BlobImageParams,
-    BlobImageRequest, BlobImageResult, DirtyRect, ImageFormat, RasterizedBlobImage, TileSize,
+    BlobImageRequest, BlobImageResources, BlobImageResult, DirtyRect, FontInstanceKey, FontKey,
+    FontTemplate, GlyphInstance, ImageFormat, RasterizedBlobImage, TileSize,
 };
 
-#[derive(Debug, Clone)]
+/// Winding rule used to fill a rasterized path or polygon, mirroring
+/// `style::values::computed::basic_shape::FillRule` without requiring a
+/// dependency on the style crate from this module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BlobFillRule {
+    NonZero,
+    EvenOdd,
+}
+
+impl From<BlobFillRule> for FillRule {
+    fn from(fill: BlobFillRule) -> Self {
+        match fill {
+            BlobFillRule::NonZero => FillRule::Winding,
+            BlobFillRule::EvenOdd => FillRule::EvenOdd,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BlobImageCommandKind {
     FillRect,
-    DrawPolygon(ManuallyDrop<Vec<webrender_api::units::LayoutPoint>>)
+    DrawPolygon(Vec<webrender_api::units::LayoutPoint>, BlobFillRule),
+    /// One or more flattened subpaths (already in device space, relative to
+    /// the mask origin), filled according to `BlobFillRule` so that
+    /// overlapping subpaths (holes) render correctly.
+    DrawPath(Vec<Vec<webrender_api::units::LayoutPoint>>, BlobFillRule),
+    /// One mask per referenced `<clipPath>` child, each with its own fill
+    /// rule, filled independently and OR-combined into the final alpha mask
+    /// (mirroring how SVG composites the children of a `clipPath` element),
+    /// rather than merged into a single path filled with one winding rule.
+    DrawPathGroup(Vec<(Vec<Vec<webrender_api::units::LayoutPoint>>, BlobFillRule)>),
+    /// A run of positioned glyphs from a single font instance, resolved to
+    /// outline paths and filled at rasterization time rather than baked into
+    /// polygons ahead of time, so the same command works at any tile scale.
+    DrawGlyphs {
+        font_instance_key: FontInstanceKey,
+        glyphs: Vec<GlyphInstance>,
+    },
+}
+
+/// Straight (non-premultiplied) RGBA color for a [`BlobPaint`], mirroring how
+/// WebRender's own example blobs carry a `ColorU` in their serialized
+/// command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl BlobColor {
+    pub const BLACK: BlobColor = BlobColor { r: 0, g: 0, b: 0, a: 255 };
+    pub const WHITE: BlobColor = BlobColor { r: 255, g: 255, b: 255, a: 255 };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlobLineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlobLineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BlobStroke {
+    pub width: f32,
+    pub line_join: BlobLineJoin,
+    pub line_cap: BlobLineCap,
+}
+
+/// Mirrors `tiny_skia::BlendMode` so the serialized command stream doesn't
+/// depend on tiny-skia's own (non-serde) type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlobBlendMode {
+    Clear,
+    Source,
+    Destination,
+    SourceOver,
+    DestinationOver,
+    SourceIn,
+    DestinationIn,
+    SourceOut,
+    DestinationOut,
+    SourceAtop,
+    DestinationAtop,
+    Xor,
+    Plus,
+    Modulate,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Multiply,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+impl Default for BlobBlendMode {
+    fn default() -> Self {
+        BlobBlendMode::SourceOver
+    }
+}
+
+impl From<BlobBlendMode> for tiny_skia::BlendMode {
+    fn from(mode: BlobBlendMode) -> Self {
+        match mode {
+            BlobBlendMode::Clear => tiny_skia::BlendMode::Clear,
+            BlobBlendMode::Source => tiny_skia::BlendMode::Source,
+            BlobBlendMode::Destination => tiny_skia::BlendMode::Destination,
+            BlobBlendMode::SourceOver => tiny_skia::BlendMode::SourceOver,
+            BlobBlendMode::DestinationOver => tiny_skia::BlendMode::DestinationOver,
+            BlobBlendMode::SourceIn => tiny_skia::BlendMode::SourceIn,
+            BlobBlendMode::DestinationIn => tiny_skia::BlendMode::DestinationIn,
+            BlobBlendMode::SourceOut => tiny_skia::BlendMode::SourceOut,
+            BlobBlendMode::DestinationOut => tiny_skia::BlendMode::DestinationOut,
+            BlobBlendMode::SourceAtop => tiny_skia::BlendMode::SourceAtop,
+            BlobBlendMode::DestinationAtop => tiny_skia::BlendMode::DestinationAtop,
+            BlobBlendMode::Xor => tiny_skia::BlendMode::Xor,
+            BlobBlendMode::Plus => tiny_skia::BlendMode::Plus,
+            BlobBlendMode::Modulate => tiny_skia::BlendMode::Modulate,
+            BlobBlendMode::Screen => tiny_skia::BlendMode::Screen,
+            BlobBlendMode::Overlay => tiny_skia::BlendMode::Overlay,
+            BlobBlendMode::Darken => tiny_skia::BlendMode::Darken,
+            BlobBlendMode::Lighten => tiny_skia::BlendMode::Lighten,
+            BlobBlendMode::ColorDodge => tiny_skia::BlendMode::ColorDodge,
+            BlobBlendMode::ColorBurn => tiny_skia::BlendMode::ColorBurn,
+            BlobBlendMode::HardLight => tiny_skia::BlendMode::HardLight,
+            BlobBlendMode::SoftLight => tiny_skia::BlendMode::SoftLight,
+            BlobBlendMode::Difference => tiny_skia::BlendMode::Difference,
+            BlobBlendMode::Exclusion => tiny_skia::BlendMode::Exclusion,
+            BlobBlendMode::Multiply => tiny_skia::BlendMode::Multiply,
+            BlobBlendMode::Hue => tiny_skia::BlendMode::Hue,
+            BlobBlendMode::Saturation => tiny_skia::BlendMode::Saturation,
+            BlobBlendMode::Color => tiny_skia::BlendMode::Color,
+            BlobBlendMode::Luminosity => tiny_skia::BlendMode::Luminosity,
+        }
+    }
+}
+
+impl From<BlobLineJoin> for tiny_skia::LineJoin {
+    fn from(join: BlobLineJoin) -> Self {
+        match join {
+            BlobLineJoin::Miter => tiny_skia::LineJoin::Miter,
+            BlobLineJoin::Round => tiny_skia::LineJoin::Round,
+            BlobLineJoin::Bevel => tiny_skia::LineJoin::Bevel,
+        }
+    }
+}
+
+impl From<BlobLineCap> for tiny_skia::LineCap {
+    fn from(cap: BlobLineCap) -> Self {
+        match cap {
+            BlobLineCap::Butt => tiny_skia::LineCap::Butt,
+            BlobLineCap::Round => tiny_skia::LineCap::Round,
+            BlobLineCap::Square => tiny_skia::LineCap::Square,
+        }
+    }
+}
+
+impl From<BlobStroke> for tiny_skia::Stroke {
+    fn from(stroke: BlobStroke) -> Self {
+        tiny_skia::Stroke {
+            width: stroke.width,
+            line_cap: stroke.line_cap.into(),
+            line_join: stroke.line_join.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Paint description embedded in a [`BlobImageCommand`]: a fill color
+/// (with alpha doubling as opacity), an antialiasing flag, a blend mode,
+/// and an optional stroke. When `stroke` is `Some`, `process_blob` strokes
+/// the command's geometry instead of filling it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BlobPaint {
+    pub color: BlobColor,
+    pub anti_alias: bool,
+    pub blend_mode: BlobBlendMode,
+    pub stroke: Option<BlobStroke>,
 }
 
-#[derive(Clone, Debug)]
+impl Default for BlobPaint {
+    fn default() -> Self {
+        Self {
+            color: BlobColor::BLACK,
+            anti_alias: true,
+            blend_mode: BlobBlendMode::default(),
+            stroke: None,
+        }
+    }
+}
+
+impl BlobPaint {
+    /// A solid-white fill with edge antialiasing enabled, so a clip mask's
+    /// boundary pixels carry partial coverage alpha instead of being
+    /// rounded to fully in/out, matching the image descriptor no longer
+    /// being marked `IS_OPAQUE`.
+    pub fn mask() -> Self {
+        Self {
+            color: BlobColor::WHITE,
+            ..Self::default()
+        }
+    }
+
+    fn to_tiny_skia(self) -> Paint<'static> {
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(self.color.r, self.color.g, self.color.b, self.color.a);
+        paint.anti_alias = self.anti_alias;
+        paint.blend_mode = self.blend_mode.into();
+        paint
+    }
+}
+
+/// A clip region applied to a command's geometry at rasterization time,
+/// rather than requiring a separate WebRender clip node. Stored alongside
+/// the command it clips so a tile can rasterize it once per distinct clip
+/// (see `ServoBlobRasterizer::resolve_mask`) and reuse it across every
+/// command that shares the same region.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BlobClip {
+    RoundedRect { rect: LayoutRect, radius: f32 },
+    Polygon(Vec<LayoutPoint>),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct BlobImageCommand {
     pub kind: BlobImageCommandKind,
     pub bounds: LayoutRect,
+    pub paint: BlobPaint,
+    pub clip: Option<BlobClip>,
 }
 
+/// Format version of the [`BlobData`] encoding below, bumped whenever the
+/// on-the-wire shape of a serialized [`BlobImageCommand`] changes, so a
+/// buffer produced by a stale version can never be misread as the current
+/// one.
+const BLOB_FORMAT_VERSION: u8 = 4;
+
+/// These are only ever used on the small, fixed-size, pointer-free
+/// [`BlobDataHeader`] — not on [`BlobImageCommand`], which is variable-length
+/// and serialized through serde/bincode instead (see [`BlobData::new_entry`]).
 #[allow(unsafe_code)]
 fn convert_to_bytes<T>(x: &T) -> &[u8] {
     let pointer = x as *const _ as *const u8;
@@ -38,8 +284,92 @@ fn convert_from_bytes<T>(x: &[u8]) -> T {
     unsafe { std::ptr::read_unaligned(x.as_ptr() as *const T) }
 }
 
+/// Appends `value` to `buffer` as a LEB128 varint.
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buffer.push(byte);
+            break;
+        } else {
+            buffer.push(byte | 0x80);
+        }
+    }
+}
+
+/// Reads a LEB128 varint starting at `data[*pos]`, advancing `*pos` past it.
+fn read_varint(data: &[u8], pos: &mut usize) -> u64 {
+    let mut result = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = data[*pos];
+        *pos += 1;
+        result |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    result
+}
+
+/// Appends a rounded rectangle to `path_builder`, approximating each corner
+/// with a cubic Bezier using the standard circle-approximation constant.
+/// Falls back to a plain rectangle when `radius`
+/// doesn't leave room for any curvature.
+fn push_rounded_rect(path_builder: &mut PathBuilder, rect: Rect, radius: f32) {
+    const KAPPA: f32 = 0.5522847498;
+    let radius = radius.min(rect.width() / 2.0).min(rect.height() / 2.0).max(0.0);
+    if radius <= 0.0 {
+        path_builder.push_rect(rect);
+        return;
+    }
+    let (left, top, right, bottom) = (rect.left(), rect.top(), rect.right(), rect.bottom());
+    let control = radius * KAPPA;
+    path_builder.move_to(left + radius, top);
+    path_builder.line_to(right - radius, top);
+    path_builder.cubic_to(
+        right - radius + control,
+        top,
+        right,
+        top + radius - control,
+        right,
+        top + radius,
+    );
+    path_builder.line_to(right, bottom - radius);
+    path_builder.cubic_to(
+        right,
+        bottom - radius + control,
+        right - radius + control,
+        bottom,
+        right - radius,
+        bottom,
+    );
+    path_builder.line_to(left + radius, bottom);
+    path_builder.cubic_to(
+        left + radius - control,
+        bottom,
+        left,
+        bottom - radius + control,
+        left,
+        bottom - radius,
+    );
+    path_builder.line_to(left, top + radius);
+    path_builder.cubic_to(
+        left,
+        top + radius - control,
+        left + radius - control,
+        top,
+        left + radius,
+        top,
+    );
+    path_builder.close();
+}
+
 #[derive(Debug, Copy, Clone, Default)]
 pub struct BlobDataHeader {
+    version: u8,
     length: usize,
 }
 
@@ -47,13 +377,13 @@ pub struct BlobDataHeader {
 pub struct BlobCommand {
     data: Arc<BlobImageData>,
     visible_rect: DeviceIntRect,
-    #[allow(dead_code)]
     tile_size: TileSize,
 }
 
 pub struct BlobData(Vec<u8>);
 pub struct BlobDataIterator<'a> {
-    current_pos: usize,
+    remaining: usize,
+    offset: usize,
     data: &'a [u8],
 }
 
@@ -61,35 +391,52 @@ impl BlobData {
     pub fn new() -> BlobData {
         let mut buffer = BlobData(Vec::new());
         buffer.0.resize(std::mem::size_of::<BlobDataHeader>(), 0);
-        buffer.write_header(BlobDataHeader::default());
+        buffer.write_header(BlobDataHeader {
+            version: BLOB_FORMAT_VERSION,
+            length: 0,
+        });
         buffer
     }
 
     pub fn new_with_capacity(capacity: usize) -> BlobData {
-        let capacity = capacity * std::mem::size_of::<BlobImageCommand>();
-        let capacity = std::mem::size_of::<BlobDataHeader>() + capacity;
+        // Commands are now variable-length, so this is only a rough sizing
+        // hint rather than an exact byte count.
+        const AVERAGE_ENTRY_SIZE: usize = 64;
+        let capacity = std::mem::size_of::<BlobDataHeader>() + capacity * AVERAGE_ENTRY_SIZE;
         let mut buffer = BlobData(Vec::with_capacity(capacity));
         buffer.0.resize(std::mem::size_of::<BlobDataHeader>(), 0);
-        buffer.write_header(BlobDataHeader::default());
+        buffer.write_header(BlobDataHeader {
+            version: BLOB_FORMAT_VERSION,
+            length: 0,
+        });
         buffer
     }
 
+    /// Serializes `data` with bincode and appends it to the buffer behind a
+    /// varint length prefix, so [`BlobDataIterator`] can read one command at
+    /// a time without knowing its encoded size in advance.
     pub fn new_entry(&mut self, data: BlobImageCommand) -> usize {
-        let header = convert_from_bytes::<BlobDataHeader>(&self.0);
-        let data = convert_to_bytes::<BlobImageCommand>(&data);
-        let new_header = BlobDataHeader {
-            length: header.length + 1,
-        };
-        self.0.extend_from_slice(&data);
-        self.write_header(new_header);
-        header.length
+        let mut header = convert_from_bytes::<BlobDataHeader>(&self.0);
+        let index = header.length;
+        let encoded =
+            bincode::serialize(&data).expect("BlobImageCommand is always serializable");
+        write_varint(&mut self.0, encoded.len() as u64);
+        self.0.extend_from_slice(&encoded);
+        header.length += 1;
+        self.write_header(header);
+        index
     }
 
+    /// Commands are variable-length, so updating one in place means
+    /// decoding the whole buffer, replacing the entry, and re-encoding it.
     pub fn update_entry(&mut self, index: usize, data: BlobImageCommand) {
-        let data = convert_to_bytes::<BlobImageCommand>(&data);
-        let size = std::mem::size_of::<BlobImageCommand>();
-        let offset = std::mem::size_of::<BlobDataHeader>() + (index * size);
-        self.0[offset..offset + size].copy_from_slice(&data);
+        let mut commands: Vec<BlobImageCommand> = BlobDataIterator::from_raw(&self.0).collect();
+        commands[index] = data;
+        let mut buffer = BlobData::new();
+        for command in commands {
+            buffer.new_entry(command);
+        }
+        *self = buffer;
     }
 
     fn write_header(&mut self, header: BlobDataHeader) {
@@ -105,8 +452,14 @@ impl BlobData {
 
 impl<'a> BlobDataIterator<'a> {
     pub fn from_raw(buffer: &'a [u8]) -> Self {
+        let header = convert_from_bytes::<BlobDataHeader>(buffer);
+        assert_eq!(
+            header.version, BLOB_FORMAT_VERSION,
+            "blob command buffer was encoded with an incompatible format version",
+        );
         Self {
-            current_pos: 0,
+            remaining: header.length,
+            offset: std::mem::size_of::<BlobDataHeader>(),
             data: buffer,
         }
     }
@@ -116,16 +469,72 @@ impl Iterator for BlobDataIterator<'_> {
     type Item = BlobImageCommand;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let header = convert_from_bytes::<BlobDataHeader>(self.data);
-        if self.current_pos < header.length {
-            let offset = std::mem::size_of::<BlobImageCommand>() * self.current_pos;
-            let offset = std::mem::size_of::<BlobDataHeader>() + offset;
-            let command = convert_from_bytes::<BlobImageCommand>(&self.data[offset..]);
-            self.current_pos += 1;
-            Some(command)
-        } else {
-            None
+        if self.remaining == 0 {
+            return None;
         }
+        let entry_len = read_varint(self.data, &mut self.offset) as usize;
+        let command = bincode::deserialize(&self.data[self.offset..self.offset + entry_len])
+            .expect("corrupt blob command entry");
+        self.offset += entry_len;
+        self.remaining -= 1;
+        Some(command)
+    }
+}
+
+/// The font data a [`DrawGlyphs`](BlobImageCommandKind::DrawGlyphs) command
+/// needs to resolve its glyphs to outlines: which font the instance was
+/// created from, and the instance's pixel size (fonts themselves are kept in
+/// a separate table, keyed by `FontKey`, since many instances commonly share
+/// one font at different sizes).
+#[derive(Debug, Clone)]
+struct FontInstance {
+    font_key: FontKey,
+    size: f32,
+}
+
+/// Adapts `ttf_parser`'s outline callbacks onto a [`PathBuilder`], placing
+/// the glyph at `origin` and scaling from font units to pixels. Font outlines
+/// are y-up; device space is y-down, so the y axis is flipped as it's
+/// accumulated into the path.
+struct GlyphPathBuilder<'a> {
+    path_builder: &'a mut PathBuilder,
+    origin: LayoutPoint,
+    scale: f32,
+}
+
+impl OutlineBuilder for GlyphPathBuilder<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.path_builder
+            .move_to(self.origin.x + x * self.scale, self.origin.y - y * self.scale);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.path_builder
+            .line_to(self.origin.x + x * self.scale, self.origin.y - y * self.scale);
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.path_builder.quad_to(
+            self.origin.x + x1 * self.scale,
+            self.origin.y - y1 * self.scale,
+            self.origin.x + x * self.scale,
+            self.origin.y - y * self.scale,
+        );
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.path_builder.cubic_to(
+            self.origin.x + x1 * self.scale,
+            self.origin.y - y1 * self.scale,
+            self.origin.x + x2 * self.scale,
+            self.origin.y - y2 * self.scale,
+            self.origin.x + x * self.scale,
+            self.origin.y - y * self.scale,
+        );
+    }
+
+    fn close(&mut self) {
+        self.path_builder.close();
     }
 }
 
@@ -134,6 +543,8 @@ pub struct ServoBlobImageHandler {
     workers: Arc<ThreadPool>,
     enable_multithreading: bool,
     blob_commands: Arc<Mutex<HashMap<BlobImageKey, BlobCommand>>>,
+    fonts: Arc<Mutex<HashMap<FontKey, Arc<Vec<u8>>>>>,
+    font_instances: Arc<Mutex<HashMap<FontInstanceKey, FontInstance>>>,
 }
 
 #[derive(Debug)]
@@ -141,6 +552,8 @@ pub struct ServoBlobRasterizer {
     workers: Arc<ThreadPool>,
     enable_multithreading: bool,
     blob_commands: Arc<Mutex<HashMap<BlobImageKey, BlobCommand>>>,
+    fonts: Arc<Mutex<HashMap<FontKey, Arc<Vec<u8>>>>>,
+    font_instances: Arc<Mutex<HashMap<FontInstanceKey, FontInstance>>>,
 }
 
 impl ServoBlobImageHandler {
@@ -157,6 +570,8 @@ impl ServoBlobImageHandler {
             workers: Arc::new(workers),
             enable_multithreading: true,
             blob_commands: Arc::new(Mutex::new(HashMap::new())),
+            fonts: Arc::new(Mutex::new(HashMap::new())),
+            font_instances: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 }
@@ -167,6 +582,8 @@ impl BlobImageHandler for ServoBlobImageHandler {
             workers: self.workers.clone(),
             enable_multithreading: self.enable_multithreading,
             blob_commands: self.blob_commands.clone(),
+            fonts: self.fonts.clone(),
+            font_instances: self.font_instances.clone(),
         })
     }
 
@@ -175,6 +592,8 @@ impl BlobImageHandler for ServoBlobImageHandler {
             workers: self.workers.clone(),
             enable_multithreading: self.enable_multithreading,
             blob_commands: self.blob_commands.clone(),
+            fonts: self.fonts.clone(),
+            font_instances: self.font_instances.clone(),
         })
     }
 
@@ -195,6 +614,13 @@ impl BlobImageHandler for ServoBlobImageHandler {
         );
     }
 
+    /// Merges a partial blob update into the previously stored one, modeled
+    /// on moz2d's `merge_blob_images`: commands are index-addressable in
+    /// both the old and new command lists, so for each index we keep the
+    /// new command if its bounds intersect the dirty rect (it was
+    /// re-recorded because it changed) and otherwise keep the old one,
+    /// rather than dropping every command that isn't wholly contained by
+    /// the dirty rect.
     fn update(
         &mut self,
         key: BlobImageKey,
@@ -203,22 +629,36 @@ impl BlobImageHandler for ServoBlobImageHandler {
         dirty_rect: &BlobDirtyRect,
     ) {
         if let Some(command) = self.blob_commands.lock().unwrap().get_mut(&key) {
-            let dirty_rect = match dirty_rect {
-                DirtyRect::All => DeviceIntRect {
-                    min: point2(i32::MIN, i32::MIN),
-                    max: point2(i32::MAX, i32::MAX),
+            let merged_commands = match dirty_rect {
+                DirtyRect::All => BlobDataIterator::from_raw(&data).collect::<Vec<_>>(),
+                DirtyRect::Partial(dirty_rect) => {
+                    let dirty_rect: LayoutRect = dirty_rect.cast_unit().cast();
+                    let old_commands: Vec<BlobImageCommand> =
+                        BlobDataIterator::from_raw(&command.data).collect();
+                    let new_commands: Vec<BlobImageCommand> =
+                        BlobDataIterator::from_raw(&data).collect();
+                    let len = old_commands.len().max(new_commands.len());
+                    (0..len)
+                        .filter_map(|i| match (new_commands.get(i), old_commands.get(i)) {
+                            (Some(new_command), Some(old_command)) => {
+                                if new_command.bounds.intersects(&dirty_rect) {
+                                    Some(new_command.clone())
+                                } else {
+                                    Some(old_command.clone())
+                                }
+                            },
+                            (Some(new_command), None) => Some(new_command.clone()),
+                            (None, Some(old_command)) => Some(old_command.clone()),
+                            (None, None) => None,
+                        })
+                        .collect()
                 },
-                DirtyRect::Partial(d) => d.cast_unit(),
             };
             let mut new_blob_data = BlobData::new();
-            let new_blob_data_iter = BlobDataIterator::from_raw(&data);
-            let preserved_rect = command.visible_rect.intersection_unchecked(visible_rect);
-            for blob_data in new_blob_data_iter {
-                if dirty_rect.contains_box(&preserved_rect) {
-                    new_blob_data.new_entry(blob_data);
-                }
+            for merged_command in merged_commands {
+                new_blob_data.new_entry(merged_command);
             }
-            command.data = Arc::new(new_blob_data.0);
+            command.data = Arc::new(new_blob_data.take());
             command.visible_rect = *visible_rect;
         }
     }
@@ -231,28 +671,111 @@ impl BlobImageHandler for ServoBlobImageHandler {
         self.enable_multithreading = enable;
     }
 
+    /// Scans the blobs referenced by `requests` for `DrawGlyphs` commands,
+    /// modeled on moz2d's `prepare_request`, and registers any font instance
+    /// (and the font it was created from) that isn't already cached, so
+    /// `process_blob` can resolve glyphs without needing access to
+    /// `services` itself.
     fn prepare_resources(
         &mut self,
-        _services: &dyn webrender_api::BlobImageResources,
-        _requests: &[webrender_api::BlobImageParams],
+        services: &dyn BlobImageResources,
+        requests: &[BlobImageParams],
     ) {
+        let blob_commands = self.blob_commands.lock().unwrap();
+        let mut fonts = self.fonts.lock().unwrap();
+        let mut font_instances = self.font_instances.lock().unwrap();
+        for request in requests {
+            let Some(command) = blob_commands.get(&request.request.key) else {
+                continue;
+            };
+            for blob_command in BlobDataIterator::from_raw(&command.data) {
+                let BlobImageCommandKind::DrawGlyphs {
+                    font_instance_key, ..
+                } = blob_command.kind
+                else {
+                    continue;
+                };
+                if font_instances.contains_key(&font_instance_key) {
+                    continue;
+                }
+                let Some(instance_data) = services.get_font_instance_data(font_instance_key)
+                else {
+                    continue;
+                };
+                if !fonts.contains_key(&instance_data.font_key) {
+                    // Native (platform) fonts have no accessible byte buffer
+                    // here, since loading them goes through Servo's font
+                    // backend rather than `ttf_parser`; such instances are
+                    // registered below but simply won't resolve any glyphs
+                    // until that integration exists.
+                    if let FontTemplate::Raw(bytes, _index) =
+                        services.get_font_data(instance_data.font_key)
+                    {
+                        fonts.insert(instance_data.font_key, bytes.clone());
+                    }
+                }
+                font_instances.insert(
+                    font_instance_key,
+                    FontInstance {
+                        font_key: instance_data.font_key,
+                        size: instance_data.size.to_f32_px(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn delete_font(&mut self, key: FontKey) {
+        self.fonts.lock().unwrap().remove(&key);
+    }
+
+    fn clear_namespace(&mut self, namespace: webrender_api::IdNamespace) {
+        self.fonts.lock().unwrap().retain(|key, _| key.0 != namespace);
+        self.font_instances
+            .lock()
+            .unwrap()
+            .retain(|key, _| key.0 != namespace);
+    }
+
+    fn delete_font_instance(&mut self, key: FontInstanceKey) {
+        self.font_instances.lock().unwrap().remove(&key);
     }
-    fn delete_font(&mut self, _key: webrender_api::FontKey) {}
-    fn clear_namespace(&mut self, _namespace: webrender_api::IdNamespace) {}
-    fn delete_font_instance(&mut self, _key: webrender_api::FontInstanceKey) {}
 }
 
 impl ServoBlobRasterizer {
-    fn process_blob(&self, pixmap: &mut Pixmap, command: BlobImageCommand) {
+    /// `transform` carries the current tile's device-pixel offset (as a
+    /// translation from full-blob-image space into this tile's local
+    /// pixmap space) so a command spanning multiple tiles paints the
+    /// correct slice into each one, instead of every tile drawing the same
+    /// command at the pixmap origin.
+    fn process_blob(
+        &self,
+        pixmap: &mut Pixmap,
+        command: BlobImageCommand,
+        transform: Transform,
+        mask_cache: &mut Vec<(BlobClip, Mask)>,
+    ) {
+        let paint = command.paint.to_tiny_skia();
+        let stroke = command.paint.stroke.map(tiny_skia::Stroke::from);
+        let mask = command.clip.as_ref().map(|clip| {
+            self.resolve_mask(clip, pixmap.width(), pixmap.height(), transform, mask_cache)
+        });
         match command.kind {
-            BlobImageCommandKind::FillRect => pixmap.fill_rect(
-                self.to_tiny_skia_rect(command.bounds),
-                &Paint::default(),
-                Transform::identity(),
-                None,
-            ),
-            BlobImageCommandKind::DrawPolygon(coordinates) => {
-                let mut coordinates = <Vec<LayoutPoint> as Clone>::clone(&coordinates.clone()).into_iter();
+            BlobImageCommandKind::FillRect => {
+                let rect = self.to_tiny_skia_rect(command.bounds);
+                match stroke {
+                    Some(stroke) => {
+                        let mut path_builder = PathBuilder::new();
+                        path_builder.push_rect(rect);
+                        if let Some(path) = path_builder.finish() {
+                            pixmap.stroke_path(&path, &paint, &stroke, transform, mask);
+                        }
+                    },
+                    None => pixmap.fill_rect(rect, &paint, transform, mask),
+                }
+            },
+            BlobImageCommandKind::DrawPolygon(coordinates, fill) => {
+                let mut coordinates = coordinates.into_iter();
                 let mut path_builder = PathBuilder::new();
                 if let Some(coordinate) = coordinates.next() {
                     path_builder.move_to(coordinate.x, coordinate.y);
@@ -262,15 +785,153 @@ impl ServoBlobRasterizer {
                 }
                 path_builder.close();
                 let path = path_builder.finish().unwrap();
-                pixmap.fill_path(
-                    &path,
-                    &Paint::default(),
-                    FillRule::Winding,
-                    Transform::identity(),
-                    None,
-                )
-            }
+                match stroke {
+                    Some(stroke) => pixmap.stroke_path(&path, &paint, &stroke, transform, mask),
+                    None => pixmap.fill_path(&path, &paint, fill.into(), transform, mask),
+                }
+            },
+            BlobImageCommandKind::DrawPath(subpaths, fill) => {
+                let mut path_builder = PathBuilder::new();
+                for subpath in subpaths {
+                    let mut points = subpath.into_iter();
+                    let Some(first) = points.next() else {
+                        continue;
+                    };
+                    path_builder.move_to(first.x, first.y);
+                    for point in points {
+                        path_builder.line_to(point.x, point.y);
+                    }
+                    path_builder.close();
+                }
+                let Some(path) = path_builder.finish() else {
+                    return;
+                };
+                match stroke {
+                    Some(stroke) => pixmap.stroke_path(&path, &paint, &stroke, transform, mask),
+                    None => pixmap.fill_path(&path, &paint, fill.into(), transform, mask),
+                }
+            },
+            BlobImageCommandKind::DrawPathGroup(groups) => {
+                // Each child is filled independently and OR-combined via
+                // `Lighten`, regardless of the command's own blend mode,
+                // since this variant always represents compositing the
+                // children of a single clip-path group onto a blank mask.
+                for (subpaths, fill) in groups {
+                    let mut path_builder = PathBuilder::new();
+                    for subpath in subpaths {
+                        let mut points = subpath.into_iter();
+                        let Some(first) = points.next() else {
+                            continue;
+                        };
+                        path_builder.move_to(first.x, first.y);
+                        for point in points {
+                            path_builder.line_to(point.x, point.y);
+                        }
+                        path_builder.close();
+                    }
+                    let Some(path) = path_builder.finish() else {
+                        continue;
+                    };
+                    let mut group_paint = paint.clone();
+                    group_paint.blend_mode = tiny_skia::BlendMode::Lighten;
+                    pixmap.fill_path(&path, &group_paint, fill.into(), transform, mask);
+                }
+            },
+            BlobImageCommandKind::DrawGlyphs {
+                font_instance_key,
+                glyphs,
+            } => {
+                let Some(path) = self.build_glyph_run_path(font_instance_key, &glyphs) else {
+                    return;
+                };
+                match stroke {
+                    Some(stroke) => pixmap.stroke_path(&path, &paint, &stroke, transform, mask),
+                    None => pixmap.fill_path(&path, &paint, FillRule::Winding, transform, mask),
+                }
+            },
+        }
+    }
+
+    /// Rasterizes `clip` into a [`Mask`] the size of the current tile,
+    /// reusing a previously rasterized mask from `mask_cache` if an earlier
+    /// command in this tile already used the same clip geometry, so a clip
+    /// shared by many commands (e.g. a single ancestor clip applied to every
+    /// descendant) is only rasterized once per tile.
+    fn resolve_mask<'a>(
+        &self,
+        clip: &BlobClip,
+        width: u32,
+        height: u32,
+        transform: Transform,
+        mask_cache: &'a mut Vec<(BlobClip, Mask)>,
+    ) -> &'a Mask {
+        if let Some(index) = mask_cache.iter().position(|(cached, _)| cached == clip) {
+            return &mask_cache[index].1;
         }
+        let mask = self.rasterize_clip_mask(clip, width, height, transform);
+        mask_cache.push((clip.clone(), mask));
+        &mask_cache.last().unwrap().1
+    }
+
+    fn rasterize_clip_mask(
+        &self,
+        clip: &BlobClip,
+        width: u32,
+        height: u32,
+        transform: Transform,
+    ) -> Mask {
+        let mut mask = Mask::new(width, height).unwrap();
+        let mut path_builder = PathBuilder::new();
+        match clip {
+            BlobClip::RoundedRect { rect, radius } => {
+                let rect = self.to_tiny_skia_rect(*rect);
+                push_rounded_rect(&mut path_builder, rect, *radius);
+            },
+            BlobClip::Polygon(points) => {
+                let mut points = points.iter();
+                if let Some(point) = points.next() {
+                    path_builder.move_to(point.x, point.y);
+                }
+                for point in points {
+                    path_builder.line_to(point.x, point.y);
+                }
+                path_builder.close();
+            },
+        }
+        if let Some(path) = path_builder.finish() {
+            mask.fill_path(&path, FillRule::Winding, true, transform);
+        }
+        mask
+    }
+
+    /// Resolves a glyph run to a single filled path covering every glyph,
+    /// returning `None` if the font instance (or its font) hasn't been
+    /// registered yet by `prepare_resources`, or if it's a native/platform
+    /// font this module can't parse.
+    fn build_glyph_run_path(
+        &self,
+        font_instance_key: FontInstanceKey,
+        glyphs: &[GlyphInstance],
+    ) -> Option<tiny_skia::Path> {
+        let instance = self
+            .font_instances
+            .lock()
+            .unwrap()
+            .get(&font_instance_key)?
+            .clone();
+        let font_data = self.fonts.lock().unwrap().get(&instance.font_key)?.clone();
+        let face = Face::parse(&font_data, 0).ok()?;
+        let scale = instance.size / face.units_per_em() as f32;
+        let mut path_builder = PathBuilder::new();
+        for glyph in glyphs {
+            let mut outline_builder = GlyphPathBuilder {
+                path_builder: &mut path_builder,
+                origin: glyph.point,
+                scale,
+            };
+            face.outline_glyph(GlyphId(glyph.index as u16), &mut outline_builder);
+        }
+        path_builder.finish()
     }
 
     fn to_tiny_skia_rect(&self, bounds: LayoutRect) -> Rect {
@@ -285,18 +946,32 @@ impl ServoBlobRasterizer {
         let rect = request.descriptor.rect;
         let mut pixmap = Pixmap::new(rect.width() as u32, rect.height() as u32).unwrap();
         let command = &self.blob_commands.lock().unwrap()[&request.request.key];
+        debug_assert!(rect.width() as u32 <= command.tile_size as u32);
+        debug_assert!(rect.height() as u32 <= command.tile_size as u32);
         let blob_data = BlobDataIterator::from_raw(&command.data);
         let dirty_rect = match request.dirty_rect {
             DirtyRect::Partial(rect) => Some(rect),
             DirtyRect::All => None,
         };
+        // Command geometry is stored in the full blob image's device space;
+        // this tile only covers `rect`, so translate every command by this
+        // tile's device-pixel offset instead of collapsing it to the origin.
+        let tile_offset = rect.min.to_vector();
+        let tile_transform = Transform::from_translate(-tile_offset.x, -tile_offset.y);
+        let mut mask_cache: Vec<(BlobClip, Mask)> = Vec::new();
         for mut command in blob_data {
             if let Some(ref dirty_rect) = dirty_rect {
-                command.bounds = command.bounds.intersection_unchecked(&dirty_rect.cast());
+                let dirty_rect = dirty_rect.cast();
+                if !command.bounds.intersects(&dirty_rect) {
+                    // This command doesn't touch the tile's dirty region at
+                    // all; shrinking it with `intersection_unchecked` below
+                    // would produce a non-positive-size rect that panics in
+                    // `to_tiny_skia_rect`, so skip it outright instead.
+                    continue;
+                }
+                command.bounds = command.bounds.intersection_unchecked(&dirty_rect);
             }
-            command.bounds = LayoutRect::from_size(command.bounds.size());
-            println!("COMMAND: {command:?}");
-            self.process_blob(&mut pixmap, command);
+            self.process_blob(&mut pixmap, command, tile_transform, &mut mask_cache);
         }
 
         #[cfg(debug_assertions)]
@@ -349,3 +1024,94 @@ impl AsyncBlobImageRasterizer for ServoBlobRasterizer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use euclid::{point2, Box2D};
+    use webrender_api::{IdNamespace, ImageKey};
+
+    use super::*;
+
+    fn fill_rect_command(bounds: LayoutRect) -> BlobImageCommand {
+        BlobImageCommand {
+            kind: BlobImageCommandKind::FillRect,
+            bounds,
+            paint: BlobPaint::default(),
+            clip: None,
+        }
+    }
+
+    fn encode(commands: Vec<BlobImageCommand>) -> Arc<BlobImageData> {
+        let mut blob_data = BlobData::new();
+        for command in commands {
+            blob_data.new_entry(command);
+        }
+        Arc::new(blob_data.take())
+    }
+
+    #[test]
+    fn update_preserves_commands_outside_the_dirty_rect() {
+        let mut handler = ServoBlobImageHandler::new();
+        let key = BlobImageKey(ImageKey::new(IdNamespace(0), 1));
+        let visible_rect: DeviceIntRect = Box2D::new(point2(0, 0), point2(100, 100));
+
+        let first = fill_rect_command(LayoutRect::new(point2(0.0, 0.0), point2(10.0, 10.0)));
+        let second = fill_rect_command(LayoutRect::new(point2(50.0, 50.0), point2(60.0, 60.0)));
+        handler.add(key, encode(vec![first, second.clone()]), &visible_rect, 256);
+
+        // Only the first command's bounds fall inside the dirty rect, so
+        // only it should be replaced by the incoming version — the second
+        // must be preserved byte-for-byte even though the incoming buffer
+        // also carries a (stale, out-of-place) copy of it.
+        let updated_first =
+            fill_rect_command(LayoutRect::new(point2(0.0, 0.0), point2(20.0, 20.0)));
+        let stale_second =
+            fill_rect_command(LayoutRect::new(point2(999.0, 999.0), point2(1000.0, 1000.0)));
+        let dirty_box: DeviceIntRect = Box2D::new(point2(0, 0), point2(15, 15));
+        let dirty_rect = BlobDirtyRect::Partial(dirty_box.cast_unit());
+        handler.update(
+            key,
+            encode(vec![updated_first.clone(), stale_second]),
+            &visible_rect,
+            &dirty_rect,
+        );
+
+        let merged: Vec<BlobImageCommand> = {
+            let commands = handler.blob_commands.lock().unwrap();
+            BlobDataIterator::from_raw(&commands[&key].data).collect()
+        };
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].bounds, updated_first.bounds);
+        assert_eq!(merged[1].bounds, second.bounds);
+        assert_eq!(
+            bincode::serialize(&merged[1]).unwrap(),
+            bincode::serialize(&second).unwrap(),
+        );
+    }
+
+    #[test]
+    fn update_with_dirty_rect_all_replaces_everything() {
+        let mut handler = ServoBlobImageHandler::new();
+        let key = BlobImageKey(ImageKey::new(IdNamespace(0), 2));
+        let visible_rect: DeviceIntRect = Box2D::new(point2(0, 0), point2(100, 100));
+
+        let original = fill_rect_command(LayoutRect::new(point2(0.0, 0.0), point2(10.0, 10.0)));
+        handler.add(key, encode(vec![original]), &visible_rect, 256);
+
+        let replacement =
+            fill_rect_command(LayoutRect::new(point2(40.0, 40.0), point2(45.0, 45.0)));
+        handler.update(
+            key,
+            encode(vec![replacement.clone()]),
+            &visible_rect,
+            &DirtyRect::All,
+        );
+
+        let merged: Vec<BlobImageCommand> = {
+            let commands = handler.blob_commands.lock().unwrap();
+            BlobDataIterator::from_raw(&commands[&key].data).collect()
+        };
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].bounds, replacement.bounds);
+    }
+}
@@ -1,15 +1,20 @@
+use std::cell::{Cell, RefCell};
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::mem::ManuallyDrop;
 
-use style::values::computed::basic_shape::{BasicShape, ClipPath, FillRule};
+use style::values::computed::basic_shape::{
+    BasicShape, ClipPath, FillRule, SvgClipPathGeometry, SvgClipPathUnits,
+};
 use style::values::computed::length::Length;
 use style::values::computed::length_percentage::{LengthPercentage, NonNegativeLengthPercentage};
 use style::values::computed::position::Position;
+use style::values::computed::url::ComputedUrl;
 use style::values::generics::basic_shape::{
     GenericPolygon, GenericShapeRadius, ShapeBox, ShapeGeometryBox,
 };
 use style::values::generics::position::GenericPositionOrAuto;
+use style::values::specified::svg_path::{CoordPair, IsAbsolute, PathCommand};
 use webrender_api::units::{LayoutPoint, LayoutRect, LayoutSize};
 use webrender_api::{
     BlobImageKey, ClipChainId, FillRule as WrFillRule, ImageDescriptor, ImageDescriptorFlags,
@@ -19,7 +24,139 @@ use webrender_traits::display_list::ScrollTreeNodeId;
 use webrender_traits::ImageUpdate;
 
 use super::{compute_marginbox_radius, normalize_radii};
-use crate::blob_rasterizer::{BlobData, BlobImageCommand, BlobImageCommandKind};
+use crate::blob_rasterizer::{
+    BlobClip, BlobData, BlobFillRule, BlobImageCommand, BlobImageCommandKind, BlobPaint,
+};
+
+/// Flattening tolerance for adaptive curve subdivision, in device pixels:
+/// a cubic is recursively split until its control points are within this
+/// distance of the chord between its endpoints.
+const PATH_FLATTENING_TOLERANCE: f32 = 0.25;
+
+/// Number of calls to [`build`] a cached clip-path mask may go unused
+/// before its blob image is torn down, mirroring how WebRender's own
+/// interning stores (e.g. `ClipDataStore`) age out stale entries. The
+/// cache's frame clock advances once per `build` call (see
+/// [`begin_clip_path_cache_frame`]), i.e. once per clip-path element in each
+/// display-list build, not once per display-list build.
+const CLIP_MASK_CACHE_MAX_AGE_FRAMES: u64 = 60;
+
+/// Snaps a mask's local bounds outward to a whole-pixel box: the mask canvas
+/// is allocated at the returned `(pixel_origin, pixel_size)` instead of the
+/// unaligned `bounds` directly, so fractional extents aren't truncated away
+/// at the right/bottom edge. Shape coordinates must then be translated by
+/// `pixel_origin` rather than `bounds.min`, carrying the sub-pixel remainder
+/// into the mask so tiny-skia's own edge-coverage antialiasing has real
+/// sub-pixel geometry to work with instead of a pre-rounded outline.
+fn pixel_align_bounds(bounds: LayoutRect) -> (LayoutPoint, LayoutSize) {
+    let pixel_origin = LayoutPoint::new(bounds.min.x.floor(), bounds.min.y.floor());
+    let pixel_size = LayoutSize::new(
+        (bounds.max.x - pixel_origin.x).ceil(),
+        (bounds.max.y - pixel_origin.y).ceil(),
+    );
+    (pixel_origin, pixel_size)
+}
+
+/// Identifies a rasterized clip-path mask independent of where it's
+/// positioned: the same shape, fill rule, and device-space size always
+/// rasterizes to the same pixels, so a scrolling or animating element that
+/// keeps the same `clip-path` can reuse one blob image across frames instead
+/// of re-rasterizing and re-uploading it on every display-list build.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ClipMaskCacheKey {
+    // One entry per subpath, tagged with the fill rule it is filled with:
+    // for `build_polygon`/`build_path` every entry carries the shape's one
+    // fill rule; for `build_url_clip_path` each `<clipPath>` child's subpaths
+    // carry that child's rule.
+    subpaths: Vec<(Vec<(i32, i32)>, BlobFillRule)>,
+    // One entry per child rasterized as a separate `BlobClip::RoundedRect`
+    // command (see `build_url_clip_path`'s handling of uniformly-rounded
+    // `rect()` children) as `(x, y, width, height, radius)` in tenths of a
+    // pixel. Always empty for `build_polygon`/`build_path`.
+    rect_clips: Vec<(i32, i32, i32, i32, i32)>,
+    width: i32,
+    height: i32,
+}
+
+impl ClipMaskCacheKey {
+    /// Rounds device-space coordinates to tenths of a pixel so ordinary
+    /// layout jitter doesn't defeat the cache while visually distinct shapes
+    /// still hash apart.
+    fn new<'a>(
+        subpaths: impl IntoIterator<Item = (&'a [LayoutPoint], BlobFillRule)>,
+        rect_clips: impl IntoIterator<Item = (LayoutRect, f32)>,
+        bounds: LayoutRect,
+    ) -> Self {
+        ClipMaskCacheKey {
+            subpaths: subpaths
+                .into_iter()
+                .map(|(subpath, fill)| {
+                    let points = subpath
+                        .iter()
+                        .map(|point| ((point.x * 10.).round() as i32, (point.y * 10.).round() as i32))
+                        .collect();
+                    (points, fill)
+                })
+                .collect(),
+            rect_clips: rect_clips
+                .into_iter()
+                .map(|(rect, radius)| {
+                    (
+                        (rect.min.x * 10.).round() as i32,
+                        (rect.min.y * 10.).round() as i32,
+                        (rect.width() * 10.).round() as i32,
+                        (rect.height() * 10.).round() as i32,
+                        (radius * 10.).round() as i32,
+                    )
+                })
+                .collect(),
+            width: bounds.width() as i32,
+            height: bounds.height() as i32,
+        }
+    }
+}
+
+struct CachedClipMask {
+    image_key: BlobImageKey,
+    last_used_frame: u64,
+}
+
+thread_local! {
+    static CLIP_MASK_CACHE: RefCell<HashMap<ClipMaskCacheKey, CachedClipMask>> =
+        RefCell::new(HashMap::new());
+    static CLIP_MASK_CACHE_FRAME: Cell<u64> = Cell::new(0);
+}
+
+/// Advances the clip-path mask cache's frame clock and evicts masks that
+/// haven't been reused in [`CLIP_MASK_CACHE_MAX_AGE_FRAMES`] calls to
+/// [`build`], queuing `DeleteImage` updates for their blob images. Called
+/// from `build` itself (see there) rather than requiring a separate per-
+/// display-list-build hook into the wider display-list builder, so the cache
+/// can't silently stop evicting just because some caller forgets to wire one
+/// in.
+fn begin_clip_path_cache_frame(display_list: &mut super::DisplayList) {
+    let frame = CLIP_MASK_CACHE_FRAME.with(|frame| {
+        let next = frame.get() + 1;
+        frame.set(next);
+        next
+    });
+    let expired = CLIP_MASK_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let mut expired = Vec::new();
+        cache.retain(|_, cached| {
+            let keep = frame - cached.last_used_frame <= CLIP_MASK_CACHE_MAX_AGE_FRAMES;
+            if !keep {
+                expired.push(cached.image_key);
+            }
+            keep
+        });
+        expired
+    });
+    if !expired.is_empty() {
+        let updates = expired.into_iter().map(ImageUpdate::DeleteImage).collect();
+        display_list.webrender_api_sender.update_images(updates);
+    }
+}
 
 pub(super) fn build(
     clip_path: ClipPath,
@@ -28,6 +165,16 @@ pub(super) fn build(
     parent_clip_chain_id: ClipChainId,
     fragment_builder: super::BuilderForBoxFragment,
 ) -> Option<ClipChainId> {
+    begin_clip_path_cache_frame(display_list);
+    if let ClipPath::Url(ref reference) = clip_path {
+        return build_url_clip_path(
+            reference,
+            fragment_builder.border_rect,
+            parent_scroll_node_id,
+            parent_clip_chain_id,
+            display_list,
+        );
+    }
     let geometry_box = match clip_path {
         ClipPath::Shape(_, ShapeGeometryBox::ShapeBox(shape_box)) => shape_box,
         ClipPath::Shape(_, ShapeGeometryBox::ElementDependent) => ShapeBox::BorderBox,
@@ -59,7 +206,13 @@ pub(super) fn build(
                     display_list,
                 )
             },
-            BasicShape::PathOrShape(_) => None,
+            BasicShape::PathOrShape(path_or_shape) => build_path(
+                &path_or_shape,
+                layout_rect,
+                parent_scroll_node_id,
+                parent_clip_chain_id,
+                display_list,
+            ),
         }
     } else {
         let layout_rect = match geometry_box {
@@ -92,9 +245,10 @@ fn build_polygon(
     parent_clip_chain_id: ClipChainId,
     display_list: &mut super::DisplayList,
 ) -> Option<ClipChainId> {
-    if polygon.coordinates.len() > POLYGON_CLIP_VERTEX_MAX {
-        return None;
-    }
+    // Above the cap, WebRender's own vertex-list clip can't represent the
+    // polygon; keep rasterizing it into a blob alpha mask and clip with
+    // that alone (empty `coordinates` below) rather than dropping the clip.
+    let exceeds_vertex_cap = polygon.coordinates.len() > POLYGON_CLIP_VERTEX_MAX;
     let webrender_api_sender = &display_list.webrender_api_sender;
     let mut bounds = None;
     let mut points = Vec::with_capacity(polygon.coordinates.len());
@@ -110,45 +264,646 @@ fn build_polygon(
         coordinates.push(coord);
     }
     let bounds = bounds?;
-    let mut updates = Vec::with_capacity(1);
-    let mut blob_data = BlobData::new_with_capacity(1);
+    let (pixel_origin, pixel_size) = pixel_align_bounds(bounds);
     for point in &mut points {
-        *point = *point - bounds.min.to_vector();
+        *point = *point - pixel_origin.to_vector();
     }
-    let command = BlobImageCommand {
-        kind: BlobImageCommandKind::DrawPolygon(ManuallyDrop::new(points)),
-        bounds: layout_rect,
+    let blob_fill = match polygon.fill {
+        FillRule::Evenodd => BlobFillRule::EvenOdd,
+        FillRule::Nonzero => BlobFillRule::NonZero,
     };
-    let descriptor = ImageDescriptor::new(
-        bounds.width() as i32,
-        bounds.height() as i32,
-        ImageFormat::RGBA8,
-        ImageDescriptorFlags::IS_OPAQUE,
+    // A scrolling or animating element whose `clip-path` doesn't change
+    // still calls this function every display-list build; reuse the blob
+    // image from a previous build rather than re-rasterizing and
+    // re-uploading identical mask pixels.
+    let cache_key = ClipMaskCacheKey::new(
+        std::iter::once((points.as_slice(), blob_fill)),
+        std::iter::empty(),
+        bounds,
     );
+    let frame = CLIP_MASK_CACHE_FRAME.with(|frame| frame.get());
+    let cached_image = CLIP_MASK_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let cached = cache.get_mut(&cache_key)?;
+        cached.last_used_frame = frame;
+        Some(cached.image_key)
+    });
+    let image = match cached_image {
+        Some(BlobImageKey(image)) => image,
+        None => {
+            let mut blob_data = BlobData::new_with_capacity(1);
+            let command = BlobImageCommand {
+                kind: BlobImageCommandKind::DrawPolygon(points, blob_fill),
+                bounds: layout_rect,
+                paint: BlobPaint::mask(),
+                clip: None,
+            };
+            let descriptor = ImageDescriptor::new(
+                pixel_size.width as i32,
+                pixel_size.height as i32,
+                ImageFormat::RGBA8,
+                ImageDescriptorFlags::empty(),
+            );
+            let image = webrender_api_sender.generate_image_key()?;
+            let blob_key = BlobImageKey(image);
+            blob_data.new_entry(command);
+            webrender_api_sender.update_images(vec![ImageUpdate::AddBlobImage(
+                blob_key,
+                descriptor,
+                Arc::new(blob_data.take()),
+            )]);
+            CLIP_MASK_CACHE.with(|cache| {
+                cache.borrow_mut().insert(
+                    cache_key,
+                    CachedClipMask {
+                        image_key: blob_key,
+                        last_used_frame: frame,
+                    },
+                );
+            });
+            image
+        },
+    };
     let image_mask = ImageMask {
-        image: webrender_api_sender.generate_image_key()?,
-        rect: bounds.translate(layout_rect.min.to_vector()),
+        image,
+        rect: LayoutRect::new(pixel_origin, pixel_origin + pixel_size)
+            .translate(layout_rect.min.to_vector()),
     };
     let fill = match polygon.fill {
         FillRule::Evenodd => WrFillRule::Evenodd,
         FillRule::Nonzero => WrFillRule::Nonzero,
     };
-    let blob_key = BlobImageKey(image_mask.image);
     let spatial_id = parent_scroll_node_id.spatial_id;
-    blob_data.new_entry(command);
-    updates.push(ImageUpdate::AddBlobImage(
-        blob_key,
-        descriptor,
-        Arc::new(blob_data.take()),
-    ));
-    webrender_api_sender.update_images(updates);
+    // Above the vertex cap, WebRender has no vertex list to approximate the
+    // clip with; the rasterized alpha mask (which tiny-skia fills using the
+    // correct winding/even-odd scanline rule) is the only geometry we pass.
+    let wr_coordinates: &[LayoutPoint] = if exceeds_vertex_cap { &[] } else { &coordinates };
+    let new_clip_id =
+        display_list
+            .wr
+            .define_clip_image_mask(spatial_id, image_mask, wr_coordinates, fill);
+    Some(display_list.define_clip_chain(parent_clip_chain_id, [new_clip_id]))
+}
+
+/// `clip-path: path(...)` / `clip-path: shape(...)`: flatten the command
+/// list into device-space subpaths and rasterize them into a blob alpha
+/// mask, exactly like `build_polygon` does for `polygon()`.
+fn build_path(
+    path_or_shape: &style::values::computed::basic_shape::PathOrShapeFunction,
+    layout_rect: LayoutRect,
+    parent_scroll_node_id: ScrollTreeNodeId,
+    parent_clip_chain_id: ClipChainId,
+    display_list: &mut super::DisplayList,
+) -> Option<ClipChainId> {
+    // `path()`'s implicit default is `nonzero`; `shape()` carries its own
+    // explicit fill keyword in the same field.
+    let fill = path_or_shape.fill.unwrap_or(FillRule::Nonzero);
+    let subpaths = flatten_path_commands(path_or_shape.commands());
+    if subpaths.iter().all(|subpath| subpath.len() < 2) {
+        return None;
+    }
+
+    let webrender_api_sender = &display_list.webrender_api_sender;
+    let mut bounds: Option<LayoutRect> = None;
+    for subpath in &subpaths {
+        for &point in subpath {
+            let point = LayoutPoint::new(point.x + layout_rect.min.x, point.y + layout_rect.min.y);
+            let current = bounds.get_or_insert(LayoutRect::new(point, point));
+            *current = LayoutRect::new(current.min.min(point), current.max.max(point));
+        }
+    }
+    let bounds = bounds?;
+    let (pixel_origin, pixel_size) = pixel_align_bounds(bounds);
+
+    let subpaths: Vec<Vec<LayoutPoint>> = subpaths
+        .into_iter()
+        .map(|subpath| {
+            subpath
+                .into_iter()
+                .map(|point| {
+                    LayoutPoint::new(
+                        point.x + layout_rect.min.x - pixel_origin.x,
+                        point.y + layout_rect.min.y - pixel_origin.y,
+                    )
+                })
+                .collect()
+        })
+        .collect();
+
+    let blob_fill = match fill {
+        FillRule::Evenodd => BlobFillRule::EvenOdd,
+        FillRule::Nonzero => BlobFillRule::NonZero,
+    };
+    // A scrolling or animating element whose `path()`/`shape()` doesn't
+    // change still calls this function every display-list build; reuse the
+    // blob image from a previous build rather than re-rasterizing and
+    // re-uploading identical mask pixels, exactly like `build_polygon`.
+    let cache_key = ClipMaskCacheKey::new(
+        subpaths.iter().map(|subpath| (subpath.as_slice(), blob_fill)),
+        std::iter::empty(),
+        bounds,
+    );
+    let frame = CLIP_MASK_CACHE_FRAME.with(|frame| frame.get());
+    let cached_image = CLIP_MASK_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let cached = cache.get_mut(&cache_key)?;
+        cached.last_used_frame = frame;
+        Some(cached.image_key)
+    });
+    let image = match cached_image {
+        Some(BlobImageKey(image)) => image,
+        None => {
+            let mut blob_data = BlobData::new_with_capacity(1);
+            let command = BlobImageCommand {
+                kind: BlobImageCommandKind::DrawPath(subpaths, blob_fill),
+                bounds: layout_rect,
+                paint: BlobPaint::mask(),
+                clip: None,
+            };
+            let descriptor = ImageDescriptor::new(
+                pixel_size.width as i32,
+                pixel_size.height as i32,
+                ImageFormat::RGBA8,
+                ImageDescriptorFlags::empty(),
+            );
+            let image = webrender_api_sender.generate_image_key()?;
+            let blob_key = BlobImageKey(image);
+            blob_data.new_entry(command);
+            webrender_api_sender.update_images(vec![ImageUpdate::AddBlobImage(
+                blob_key,
+                descriptor,
+                Arc::new(blob_data.take()),
+            )]);
+            CLIP_MASK_CACHE.with(|cache| {
+                cache.borrow_mut().insert(
+                    cache_key,
+                    CachedClipMask {
+                        image_key: blob_key,
+                        last_used_frame: frame,
+                    },
+                );
+            });
+            image
+        },
+    };
+    // Unlike `build_polygon`, `bounds` above is already in absolute
+    // (layout_rect-relative-to-the-page) coordinates, so `pixel_origin` is
+    // too — it must not be translated by `layout_rect.min` again here.
+    let image_mask = ImageMask {
+        image,
+        rect: LayoutRect::new(pixel_origin, pixel_origin + pixel_size),
+    };
+    let spatial_id = parent_scroll_node_id.spatial_id;
+    // There is no discrete vertex list WebRender's own approximate clip can
+    // use for curved edges, so rely entirely on the rasterized alpha mask.
+    let new_clip_id =
+        display_list
+            .wr
+            .define_clip_image_mask(spatial_id, image_mask, &[], WrFillRule::Nonzero);
+    Some(display_list.define_clip_chain(parent_clip_chain_id, [new_clip_id]))
+}
+
+/// `clip-path: url(#id)`: resolve the referenced SVG `<clipPath>` element,
+/// flatten each child shape in its own `clipPathUnits` space, and rasterize
+/// them as independently-filled, OR-combined subpaths into one alpha mask
+/// (see [`BlobImageCommandKind::DrawPathGroup`]), except for uniformly
+/// circular-rounded `rect()` children, which are instead rasterized as
+/// their own `BlobClip::RoundedRect`-masked rectangle (see the loop below).
+/// An unresolvable or missing reference returns `None`, which `build`
+/// reports as "no clip" rather than clipping to nothing.
+fn build_url_clip_path(
+    reference: &ComputedUrl,
+    layout_rect: LayoutRect,
+    parent_scroll_node_id: ScrollTreeNodeId,
+    parent_clip_chain_id: ClipChainId,
+    display_list: &mut super::DisplayList,
+) -> Option<ClipChainId> {
+    let geometry = reference.resolved_clip_path()?;
+    let webrender_api_sender = &display_list.webrender_api_sender;
+
+    let mut bounds: Option<LayoutRect> = None;
+    let mut groups = Vec::with_capacity(geometry.children.len());
+    // `rect()` children rounded by a single circular radius (the common
+    // case) are rasterized as a separate filled-and-clipped rectangle via
+    // `BlobClip::RoundedRect` instead of polygon subpaths: it represents
+    // that shape exactly, with no curve flattening needed. Only handled for
+    // `userSpaceOnUse`, where the rect needs no percentage-to-absolute
+    // scaling that would otherwise turn its corner radius non-circular;
+    // other `rect()` children (non-uniform corner radii, or any shape under
+    // `objectBoundingBox`) fall through to `shape_subpaths_in_rect`, which
+    // flattens `circle()`/`ellipse()` to a polygon but contributes no
+    // geometry for any `rect()`, so such a child adds nothing to the mask.
+    let mut rect_clips = Vec::new();
+    for (shape, fill) in &geometry.children {
+        if matches!(geometry.units, SvgClipPathUnits::UserSpaceOnUse) {
+            if let BasicShape::Rect(rect) = shape {
+                let top = rect.rect.0.resolve(Length::new(layout_rect.height()));
+                let right = rect.rect.1.resolve(Length::new(layout_rect.width()));
+                let bottom = rect.rect.2.resolve(Length::new(layout_rect.height()));
+                let left = rect.rect.3.resolve(Length::new(layout_rect.width()));
+                let origin = LayoutPoint::new(layout_rect.min.x + left.px(), layout_rect.min.y + top.px());
+                let size = LayoutSize::new(
+                    layout_rect.width() - (left + right).px(),
+                    layout_rect.height() - (top + bottom).px(),
+                );
+                let rounded_rect = LayoutRect::from_origin_and_size(origin, size);
+                let resolve = |radius: &LengthPercentage, box_size: f32| {
+                    radius.percentage_relative_to(Length::new(box_size)).px()
+                };
+                let corner = |corner: &style::values::computed::BorderCornerRadius| {
+                    LayoutSize::new(
+                        resolve(&corner.0.width.0, layout_rect.size().width),
+                        resolve(&corner.0.height.0, layout_rect.size().height),
+                    )
+                };
+                let top_left = corner(&rect.round.top_left);
+                let is_uniform_circular_radius = top_left.width == top_left.height &&
+                    top_left == corner(&rect.round.top_right) &&
+                    top_left == corner(&rect.round.bottom_left) &&
+                    top_left == corner(&rect.round.bottom_right);
+                if is_uniform_circular_radius {
+                    let current = bounds.get_or_insert(rounded_rect);
+                    *current =
+                        LayoutRect::new(current.min.min(rounded_rect.min), current.max.max(rounded_rect.max));
+                    rect_clips.push((rounded_rect, top_left.width));
+                    continue;
+                }
+            }
+        }
+        let subpaths = match geometry.units {
+            SvgClipPathUnits::ObjectBoundingBox => {
+                let unit_square = LayoutRect::from_size(LayoutSize::new(1., 1.));
+                shape_subpaths_in_rect(shape, unit_square)
+                    .into_iter()
+                    .map(|subpath| {
+                        subpath
+                            .into_iter()
+                            .map(|point| {
+                                LayoutPoint::new(
+                                    layout_rect.min.x + point.x * layout_rect.width(),
+                                    layout_rect.min.y + point.y * layout_rect.height(),
+                                )
+                            })
+                            .collect()
+                    })
+                    .collect()
+            },
+            SvgClipPathUnits::UserSpaceOnUse => shape_subpaths_in_rect(shape, layout_rect),
+        };
+        if subpaths.iter().all(|subpath: &Vec<LayoutPoint>| subpath.len() < 2) {
+            continue;
+        }
+        for subpath in &subpaths {
+            for &point in subpath {
+                let current = bounds.get_or_insert(LayoutRect::new(point, point));
+                *current = LayoutRect::new(current.min.min(point), current.max.max(point));
+            }
+        }
+        let blob_fill = match fill {
+            FillRule::Evenodd => BlobFillRule::EvenOdd,
+            FillRule::Nonzero => BlobFillRule::NonZero,
+        };
+        groups.push((subpaths, blob_fill));
+    }
+    let bounds = bounds?;
+    let (pixel_origin, pixel_size) = pixel_align_bounds(bounds);
+
+    let groups: Vec<(Vec<Vec<LayoutPoint>>, BlobFillRule)> = groups
+        .into_iter()
+        .map(|(subpaths, fill)| {
+            let subpaths = subpaths
+                .into_iter()
+                .map(|subpath| {
+                    subpath
+                        .into_iter()
+                        .map(|point| point - pixel_origin.to_vector())
+                        .collect()
+                })
+                .collect();
+            (subpaths, fill)
+        })
+        .collect();
+    let rect_clips: Vec<(LayoutRect, f32)> = rect_clips
+        .into_iter()
+        .map(|(rect, radius)| (rect.translate(-pixel_origin.to_vector()), radius))
+        .collect();
+
+    // A scrolling or animating element whose `url(#id)` clip path doesn't
+    // change still calls this function every display-list build; reuse the
+    // blob image from a previous build rather than re-rasterizing and
+    // re-uploading identical mask pixels, exactly like `build_polygon`.
+    let cache_key = ClipMaskCacheKey::new(
+        groups
+            .iter()
+            .flat_map(|(subpaths, fill)| subpaths.iter().map(move |subpath| (subpath.as_slice(), *fill))),
+        rect_clips.iter().copied(),
+        bounds,
+    );
+    let frame = CLIP_MASK_CACHE_FRAME.with(|frame| frame.get());
+    let cached_image = CLIP_MASK_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        let cached = cache.get_mut(&cache_key)?;
+        cached.last_used_frame = frame;
+        Some(cached.image_key)
+    });
+    let image = match cached_image {
+        Some(BlobImageKey(image)) => image,
+        None => {
+            let mut blob_data = BlobData::new_with_capacity(1 + rect_clips.len());
+            if !groups.is_empty() {
+                blob_data.new_entry(BlobImageCommand {
+                    kind: BlobImageCommandKind::DrawPathGroup(groups),
+                    bounds: layout_rect,
+                    paint: BlobPaint::mask(),
+                    clip: None,
+                });
+            }
+            for (rect, radius) in rect_clips {
+                blob_data.new_entry(BlobImageCommand {
+                    kind: BlobImageCommandKind::FillRect,
+                    bounds: rect,
+                    paint: BlobPaint::mask(),
+                    clip: Some(BlobClip::RoundedRect { rect, radius }),
+                });
+            }
+            let descriptor = ImageDescriptor::new(
+                pixel_size.width as i32,
+                pixel_size.height as i32,
+                ImageFormat::RGBA8,
+                ImageDescriptorFlags::empty(),
+            );
+            let image = webrender_api_sender.generate_image_key()?;
+            let blob_key = BlobImageKey(image);
+            webrender_api_sender.update_images(vec![ImageUpdate::AddBlobImage(
+                blob_key,
+                descriptor,
+                Arc::new(blob_data.take()),
+            )]);
+            CLIP_MASK_CACHE.with(|cache| {
+                cache.borrow_mut().insert(
+                    cache_key,
+                    CachedClipMask {
+                        image_key: blob_key,
+                        last_used_frame: frame,
+                    },
+                );
+            });
+            image
+        },
+    };
+    // As in `build_path`, `bounds` (and so `pixel_origin`) above is already
+    // absolute, so translating by `layout_rect.min` again would double-shift
+    // the mask off its element.
+    let image_mask = ImageMask {
+        image,
+        rect: LayoutRect::new(pixel_origin, pixel_origin + pixel_size),
+    };
+    let spatial_id = parent_scroll_node_id.spatial_id;
     let new_clip_id =
         display_list
             .wr
-            .define_clip_image_mask(spatial_id, image_mask, &coordinates, fill);
+            .define_clip_image_mask(spatial_id, image_mask, &[], WrFillRule::Nonzero);
     Some(display_list.define_clip_chain(parent_clip_chain_id, [new_clip_id]))
 }
 
+/// Number of straight segments used to flatten a `circle()`/`ellipse()`
+/// `<clipPath>` child into a polygon: the same idea as
+/// [`PATH_FLATTENING_TOLERANCE`], just a fixed count rather than an
+/// error-bounded subdivision, since there's no single chord-length tolerance
+/// that makes sense across arbitrary shape sizes here.
+const ELLIPSE_POLYGON_SEGMENTS: usize = 64;
+
+/// Flattens an axis-aligned ellipse centered at `center` with semi-axes
+/// `radii` into a closed polygon, in the same coordinate space as `center`.
+fn ellipse_subpath(center: LayoutPoint, radii: LayoutSize) -> Vec<LayoutPoint> {
+    (0..ELLIPSE_POLYGON_SEGMENTS)
+        .map(|i| {
+            let angle = 2. * std::f32::consts::PI * (i as f32) / (ELLIPSE_POLYGON_SEGMENTS as f32);
+            LayoutPoint::new(
+                center.x + radii.width * angle.cos(),
+                center.y + radii.height * angle.sin(),
+            )
+        })
+        .collect()
+}
+
+/// Flattens one `<clipPath>` child shape into device-space subpaths within
+/// `rect`, reusing the same polygon/path flattening `build_polygon`/
+/// `build_path` use for the standalone `polygon()`/`path()`/`shape()`
+/// functions, and the same center/radius resolution [`build_simple_shape`]
+/// uses for standalone `circle()`/`ellipse()`. A `rect()` child is not
+/// flattened, whatever its corner radii, and contributes no geometry:
+/// `build_url_clip_path` rasterizes uniformly circular-rounded `rect()`
+/// children under `userSpaceOnUse` itself via [`BlobClip::RoundedRect`]
+/// without calling this, and every other `rect()` child adds nothing to the
+/// mask.
+fn shape_subpaths_in_rect(shape: &BasicShape, rect: LayoutRect) -> Vec<Vec<LayoutPoint>> {
+    match shape {
+        BasicShape::Polygon(polygon) => {
+            let mut subpath = Vec::with_capacity(polygon.coordinates.len());
+            for coordinate in &polygon.coordinates {
+                let (x, y) = coordinate.clone();
+                let x = x.resolve(Length::new(rect.width()));
+                let y = y.resolve(Length::new(rect.height()));
+                subpath.push(LayoutPoint::new(rect.min.x + x.px(), rect.min.y + y.px()));
+            }
+            vec![subpath]
+        },
+        BasicShape::PathOrShape(path_or_shape) => flatten_path_commands(path_or_shape.commands())
+            .into_iter()
+            .map(|subpath| {
+                subpath
+                    .into_iter()
+                    .map(|point| LayoutPoint::new(rect.min.x + point.x, rect.min.y + point.y))
+                    .collect()
+            })
+            .collect(),
+        BasicShape::Circle(circle) => {
+            let center = match circle.position {
+                GenericPositionOrAuto::Position(position) => position,
+                GenericPositionOrAuto::Auto => Position::center(),
+            };
+            let anchor_x = center.horizontal.resolve(Length::new(rect.width()));
+            let anchor_y = center.vertical.resolve(Length::new(rect.height()));
+            let center = rect.min.add_size(&LayoutSize::new(anchor_x.px(), anchor_y.px()));
+            let horizontal = compute_shape_radius(center.x, &circle.radius, rect.min.x, rect.max.x);
+            let vertical = compute_shape_radius(center.y, &circle.radius, rect.min.y, rect.max.y);
+            let radius = match circle.radius {
+                GenericShapeRadius::FarthestSide => horizontal.max(vertical),
+                _ => horizontal.min(vertical),
+            };
+            vec![ellipse_subpath(center, LayoutSize::new(radius, radius))]
+        },
+        BasicShape::Ellipse(ellipse) => {
+            let center = match ellipse.position {
+                GenericPositionOrAuto::Position(position) => position,
+                GenericPositionOrAuto::Auto => Position::center(),
+            };
+            let anchor_x = center.horizontal.resolve(Length::new(rect.width()));
+            let anchor_y = center.vertical.resolve(Length::new(rect.height()));
+            let center = rect.min.add_size(&LayoutSize::new(anchor_x.px(), anchor_y.px()));
+            let width = if let GenericShapeRadius::Length(length) = ellipse.semiaxis_x {
+                length.0.resolve(Length::new(rect.width())).px()
+            } else {
+                compute_shape_radius(center.x, &ellipse.semiaxis_x, rect.min.x, rect.max.x)
+            };
+            let height = if let GenericShapeRadius::Length(length) = ellipse.semiaxis_y {
+                length.0.resolve(Length::new(rect.height())).px()
+            } else {
+                compute_shape_radius(center.y, &ellipse.semiaxis_y, rect.min.y, rect.max.y)
+            };
+            vec![ellipse_subpath(center, LayoutSize::new(width, height))]
+        },
+        BasicShape::Rect(_) => Vec::new(),
+    }
+}
+
+/// Flatten a `path()`/`shape()` command list into one polyline per subpath
+/// (a `moveto` starts a new subpath; `closepath` closes the current one),
+/// recursively subdividing curves to within [`PATH_FLATTENING_TOLERANCE`].
+fn flatten_path_commands(commands: &[PathCommand]) -> Vec<Vec<CoordPair>> {
+    let mut subpaths: Vec<Vec<CoordPair>> = Vec::new();
+    let mut current = CoordPair::new(0., 0.);
+    let mut subpath_start = current;
+
+    let resolve = |current: CoordPair, point: CoordPair, absolute: IsAbsolute| match absolute {
+        IsAbsolute::Yes => point,
+        IsAbsolute::No => CoordPair::new(current.x + point.x, current.y + point.y),
+    };
+
+    for command in commands {
+        match *command {
+            PathCommand::Unknown => {},
+            PathCommand::ClosePath => {
+                if let Some(subpath) = subpaths.last_mut() {
+                    subpath.push(subpath_start);
+                }
+                current = subpath_start;
+            },
+            PathCommand::MoveTo { point, absolute } => {
+                current = resolve(current, point, absolute);
+                subpath_start = current;
+                subpaths.push(vec![current]);
+            },
+            PathCommand::LineTo { point, absolute } => {
+                current = resolve(current, point, absolute);
+                push_point(&mut subpaths, current);
+            },
+            PathCommand::HorizontalLineTo { x, absolute } => {
+                current = resolve(current, CoordPair::new(x, 0.), absolute);
+                push_point(&mut subpaths, current);
+            },
+            PathCommand::VerticalLineTo { y, absolute } => {
+                current = resolve(current, CoordPair::new(0., y), absolute);
+                push_point(&mut subpaths, current);
+            },
+            PathCommand::CurveTo {
+                control1,
+                control2,
+                point,
+                absolute,
+            } => {
+                let control1 = resolve(current, control1, absolute);
+                let control2 = resolve(current, control2, absolute);
+                let end = resolve(current, point, absolute);
+                flatten_cubic(current, control1, control2, end, 0, &mut subpaths);
+                current = end;
+            },
+            PathCommand::SmoothCurveTo {
+                control2,
+                point,
+                absolute,
+            } => {
+                let control1 = current;
+                let control2 = resolve(current, control2, absolute);
+                let end = resolve(current, point, absolute);
+                flatten_cubic(current, control1, control2, end, 0, &mut subpaths);
+                current = end;
+            },
+            PathCommand::QuadBezierCurveTo {
+                control1,
+                point,
+                absolute,
+            } => {
+                let control1 = resolve(current, control1, absolute);
+                let end = resolve(current, point, absolute);
+                flatten_quadratic(current, control1, end, 0, &mut subpaths);
+                current = end;
+            },
+            PathCommand::SmoothQuadBezierCurveTo { point, absolute } => {
+                let control1 = current;
+                let end = resolve(current, point, absolute);
+                flatten_quadratic(current, control1, end, 0, &mut subpaths);
+                current = end;
+            },
+            PathCommand::EllipticalArc { point, absolute, .. } => {
+                // Approximate the arc with a straight segment to its
+                // endpoint; full ellipse-to-bezier conversion is out of
+                // scope for this flattening pass.
+                current = resolve(current, point, absolute);
+                push_point(&mut subpaths, current);
+            },
+        }
+    }
+
+    subpaths
+}
+
+fn push_point(subpaths: &mut [Vec<CoordPair>], point: CoordPair) {
+    if let Some(subpath) = subpaths.last_mut() {
+        subpath.push(point);
+    }
+}
+
+fn flatten_cubic(
+    p0: CoordPair,
+    p1: CoordPair,
+    p2: CoordPair,
+    p3: CoordPair,
+    depth: u8,
+    subpaths: &mut [Vec<CoordPair>],
+) {
+    if depth >= 24 || cubic_is_flat_enough(p0, p1, p2, p3) {
+        push_point(subpaths, p3);
+        return;
+    }
+    let p01 = midpoint(p0, p1);
+    let p12 = midpoint(p1, p2);
+    let p23 = midpoint(p2, p3);
+    let p012 = midpoint(p01, p12);
+    let p123 = midpoint(p12, p23);
+    let p0123 = midpoint(p012, p123);
+    flatten_cubic(p0, p01, p012, p0123, depth + 1, subpaths);
+    flatten_cubic(p0123, p123, p23, p3, depth + 1, subpaths);
+}
+
+fn flatten_quadratic(
+    p0: CoordPair,
+    p1: CoordPair,
+    p2: CoordPair,
+    depth: u8,
+    subpaths: &mut [Vec<CoordPair>],
+) {
+    // Elevate to an equivalent cubic so we can share the cubic flattener.
+    let c1 = CoordPair::new(p0.x + 2. / 3. * (p1.x - p0.x), p0.y + 2. / 3. * (p1.y - p0.y));
+    let c2 = CoordPair::new(p2.x + 2. / 3. * (p1.x - p2.x), p2.y + 2. / 3. * (p1.y - p2.y));
+    flatten_cubic(p0, c1, c2, p2, depth, subpaths);
+}
+
+fn midpoint(a: CoordPair, b: CoordPair) -> CoordPair {
+    CoordPair::new((a.x + b.x) / 2., (a.y + b.y) / 2.)
+}
+
+/// Distance of each control point from the chord `p0`-`p3`, used to decide
+/// whether a cubic segment is flat enough to stop subdividing.
+fn cubic_is_flat_enough(p0: CoordPair, p1: CoordPair, p2: CoordPair, p3: CoordPair) -> bool {
+    let ux = (3. * p1.x - 2. * p0.x - p3.x).powi(2);
+    let uy = (3. * p1.y - 2. * p0.y - p3.y).powi(2);
+    let vx = (3. * p2.x - 2. * p3.x - p0.x).powi(2);
+    let vy = (3. * p2.y - 2. * p3.y - p0.y).powi(2);
+    (ux.max(vx) + uy.max(vy)) <= 16. * PATH_FLATTENING_TOLERANCE * PATH_FLATTENING_TOLERANCE
+}
+
 fn build_simple_shape(
     shape: BasicShape,
     layout: LayoutRect,
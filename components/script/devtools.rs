@@ -2,18 +2,20 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::str;
 
 use base::id::PipelineId;
 use devtools_traits::{
     AttrModification, AutoMargins, ComputedNodeLayout, CssDatabaseProperty, EvaluateJSReply,
-    NodeInfo, NodeStyle, RuleModification, TimelineMarker, TimelineMarkerType,
+    NodeInfo, NodeStyle, PreviewProperty, PreviewValue, RuleModification, RuleTarget,
+    SelectorInfo, Specificity, TimelineMarker, TimelineMarkerType,
 };
 use ipc_channel::ipc::IpcSender;
 use js::jsval::UndefinedValue;
-use js::rust::ToString;
+use js::rust::{HandleValue, ToString};
 use uuid::Uuid;
 
 use crate::dom::bindings::codegen::Bindings::CSSRuleListBinding::CSSRuleListMethods;
@@ -30,7 +32,9 @@ use crate::dom::bindings::conversions::{jsstring_to_str, ConversionResult, FromJ
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
-use crate::dom::cssstyledeclaration::ENABLED_LONGHAND_PROPERTIES;
+use crate::dom::cssstyledeclaration::{
+    CSSStyleDeclaration, ENABLED_LONGHAND_PROPERTIES, ENABLED_SHORTHAND_PROPERTIES,
+};
 use crate::dom::cssstylerule::CSSStyleRule;
 use crate::dom::document::AnimationFrameCallback;
 use crate::dom::element::Element;
@@ -42,6 +46,208 @@ use crate::realms::enter_realm;
 use crate::script_module::ScriptFetchOptions;
 use crate::script_thread::Documents;
 
+/// Maximum number of own enumerable properties included in an object preview.
+const MAX_PREVIEW_PROPERTIES: usize = 10;
+/// Maximum depth of nested object grips before we fall back to a bare class name.
+const MAX_PREVIEW_DEPTH: u32 = 2;
+
+/// Build a [`PreviewValue`] for `val`, recursing into objects up to
+/// [`MAX_PREVIEW_DEPTH`] and guarding against reference cycles via `visited`
+/// (keyed on the object's pointer address).
+#[allow(unsafe_code)]
+fn build_preview_value(
+    cx: *mut js::jsapi::JSContext,
+    val: HandleValue,
+    depth: u32,
+    visited: &mut HashSet<usize>,
+) -> PreviewValue {
+    if val.is_undefined() {
+        PreviewValue::Undefined
+    } else if val.is_null() {
+        PreviewValue::Null
+    } else if val.is_boolean() {
+        PreviewValue::Boolean(val.to_boolean())
+    } else if val.is_double() || val.is_int32() {
+        PreviewValue::Number(match FromJSValConvertible::from_jsval(cx, val, ()) {
+            Ok(ConversionResult::Success(v)) => v,
+            _ => 0.0,
+        })
+    } else if val.is_string() {
+        let jsstr = std::ptr::NonNull::new(val.to_string()).unwrap();
+        PreviewValue::String(String::from(jsstring_to_str(cx, jsstr)))
+    } else {
+        assert!(val.is_object());
+        let obj_ptr = val.to_object() as usize;
+        let class = match std::ptr::NonNull::new(unsafe { ToString(cx, val) }) {
+            Some(jsstr) => jsstring_to_str(cx, jsstr).to_string(),
+            None => {
+                // Coercing the object to a string threw (e.g. a throwing
+                // `toString`/`Symbol.toPrimitive`); clear it and fall back to
+                // a generic class name instead of panicking the preview.
+                unsafe { js::jsapi::JS_ClearPendingException(cx) };
+                "Object".to_string()
+            },
+        };
+
+        if depth >= MAX_PREVIEW_DEPTH || !visited.insert(obj_ptr) {
+            return PreviewValue::Grip { class };
+        }
+        let preview = build_object_preview(cx, val, depth, class.clone(), visited);
+        visited.remove(&obj_ptr);
+        preview
+    }
+}
+
+/// Walk the own enumerable properties of a JS object (capped at
+/// [`MAX_PREVIEW_PROPERTIES`]) and build a structured console preview:
+/// arrays/typed arrays get their length and first elements, functions get
+/// their name and parameter arity, and everything else gets a name/value
+/// property list. Getters that throw are reported as accessors rather than
+/// propagating the exception.
+#[allow(unsafe_code)]
+fn build_object_preview(
+    cx: *mut js::jsapi::JSContext,
+    object: HandleValue,
+    depth: u32,
+    class: String,
+    visited: &mut HashSet<usize>,
+) -> PreviewValue {
+    rooted!(in(cx) let obj = object.to_object());
+
+    if let Some((length, elements)) = js::rust::try_get_array_length_and_elements(cx, obj.handle())
+    {
+        let elements = elements
+            .into_iter()
+            .take(MAX_PREVIEW_PROPERTIES)
+            .map(|el| {
+                rooted!(in(cx) let el = el);
+                build_preview_value(cx, el.handle(), depth + 1, visited)
+            })
+            .collect();
+        return PreviewValue::Array { length, elements };
+    }
+
+    if let Some((name, arity)) = js::rust::try_get_function_name_and_arity(cx, obj.handle()) {
+        return PreviewValue::Function { name, arity };
+    }
+
+    rooted!(in(cx) let mut ids = js::rust::IdVector::new(cx));
+    if !unsafe {
+        js::jsapi::GetPropertyKeys(
+            cx,
+            obj.handle().into(),
+            js::jsapi::JSITER_OWNONLY,
+            ids.as_mut(),
+        )
+    } {
+        unsafe { js::jsapi::JS_ClearPendingException(cx) };
+        return PreviewValue::Grip { class };
+    }
+
+    let mut properties = Vec::new();
+    for id in ids.iter().take(MAX_PREVIEW_PROPERTIES) {
+        rooted!(in(cx) let id = *id);
+        let Some(name) = jsid_to_preview_name(cx, id.handle()) else {
+            continue;
+        };
+
+        rooted!(in(cx) let mut prop_val = UndefinedValue());
+        let got = unsafe {
+            js::jsapi::JS_GetPropertyById(cx, obj.handle(), id.handle(), prop_val.handle_mut())
+        };
+        let value = if got {
+            build_preview_value(cx, prop_val.handle(), depth + 1, visited)
+        } else {
+            // The getter threw; clear the exception and mark this slot as an
+            // accessor rather than aborting the whole preview.
+            unsafe { js::jsapi::JS_ClearPendingException(cx) };
+            PreviewValue::Accessor
+        };
+
+        properties.push(PreviewProperty { name, value });
+    }
+
+    PreviewValue::Object { class, properties }
+}
+
+#[allow(unsafe_code)]
+fn jsid_to_preview_name(cx: *mut js::jsapi::JSContext, id: js::rust::HandleId) -> Option<String> {
+    rooted!(in(cx) let mut id_val = UndefinedValue());
+    unsafe { js::jsapi::JS_IdToValue(cx, id.get(), id_val.handle_mut()) };
+    if !id_val.is_string() {
+        return None;
+    }
+    let jsstr = std::ptr::NonNull::new(id_val.to_string())?;
+    Some(String::from(jsstring_to_str(cx, jsstr)))
+}
+
+/// Read a named string property off a rooted JS object, returning `None` if
+/// it is absent or not a string (e.g. `Error.prototype.stack` when no
+/// SpiderMonkey saved frame is available), or if its getter throws (the
+/// exception is cleared).
+#[allow(unsafe_code)]
+fn get_string_property(
+    cx: *mut js::jsapi::JSContext,
+    obj: js::rust::HandleObject,
+    name: &str,
+) -> Option<String> {
+    let cname = std::ffi::CString::new(name).ok()?;
+    rooted!(in(cx) let mut val = UndefinedValue());
+    let found = unsafe { js::jsapi::JS_GetProperty(cx, obj, cname.as_ptr(), val.handle_mut()) };
+    if !found {
+        unsafe { js::jsapi::JS_ClearPendingException(cx) };
+        return None;
+    }
+    if !val.is_string() {
+        return None;
+    }
+    let jsstr = std::ptr::NonNull::new(val.to_string())?;
+    Some(String::from(jsstring_to_str(cx, jsstr)))
+}
+
+/// Convert a pending exception into an [`EvaluateJSReply::ExceptionValue`],
+/// clearing it from the `JSContext` so the thread is left in a clean state.
+#[allow(unsafe_code)]
+fn build_exception_reply(cx: *mut js::jsapi::JSContext) -> EvaluateJSReply {
+    rooted!(in(cx) let mut thrown = UndefinedValue());
+    unsafe {
+        js::jsapi::JS_GetPendingException(cx, thrown.handle_mut());
+        js::jsapi::JS_ClearPendingException(cx);
+    }
+
+    if thrown.is_object() {
+        rooted!(in(cx) let obj = thrown.to_object());
+        if let Some(message) = get_string_property(cx, obj.handle(), "message") {
+            let class = get_string_property(cx, obj.handle(), "name")
+                .unwrap_or_else(|| "Error".to_string());
+            let stack = get_string_property(cx, obj.handle(), "stack");
+            return EvaluateJSReply::ExceptionValue {
+                class,
+                message,
+                stack,
+            };
+        }
+    }
+
+    // Not an Error instance (e.g. `throw "oops"` or `throw 42`): coerce
+    // whatever was thrown to a string for display.
+    let message = match std::ptr::NonNull::new(unsafe { ToString(cx, thrown.handle()) }) {
+        Some(jsstr) => String::from(jsstring_to_str(cx, jsstr)),
+        None => {
+            // Coercing the thrown value to a string itself threw (e.g.
+            // `throw Symbol()`); clear that exception too and fall back to a
+            // placeholder rather than panicking the script thread.
+            unsafe { js::jsapi::JS_ClearPendingException(cx) };
+            "<exception>".to_string()
+        },
+    };
+    EvaluateJSReply::ExceptionValue {
+        class: "Error".to_string(),
+        message,
+        stack: None,
+    }
+}
+
 #[allow(unsafe_code)]
 pub fn handle_evaluate_js(global: &GlobalScope, eval: String, reply: IpcSender<EvaluateJSReply>) {
     // global.get_cx() returns a valid `JSContext` pointer, so this is safe.
@@ -59,7 +265,9 @@ pub fn handle_evaluate_js(global: &GlobalScope, eval: String, reply: IpcSender<E
             global.api_base_url(),
         );
 
-        if rval.is_undefined() {
+        if js::jsapi::JS_IsExceptionPending(*cx) {
+            build_exception_reply(*cx)
+        } else if rval.is_undefined() {
             EvaluateJSReply::VoidValue
         } else if rval.is_boolean() {
             EvaluateJSReply::BooleanValue(rval.to_boolean())
@@ -81,9 +289,13 @@ pub fn handle_evaluate_js(global: &GlobalScope, eval: String, reply: IpcSender<E
             let jsstr = std::ptr::NonNull::new(ToString(*cx, rval.handle())).unwrap();
             let class_name = jsstring_to_str(*cx, jsstr);
 
+            let mut visited = HashSet::new();
+            let preview = build_preview_value(*cx, rval.handle(), 0, &mut visited);
+
             EvaluateJSReply::ActorValue {
                 class: class_name.to_string(),
                 uuid: Uuid::new_v4().to_string(),
+                preview: Some(preview),
             }
         }
     };
@@ -126,6 +338,37 @@ fn find_node_by_unique_id(
     })
 }
 
+/// Generated-content pseudo-elements that can be selected in the markup
+/// tree, in the order they should appear relative to real children.
+const GENERATED_PSEUDO_ELEMENTS: &[&str] = &["::marker", "::before", "::after", "::selection"];
+
+/// Build a synthetic [`NodeInfo`] for a generated pseudo-element, so
+/// devtools can select and inspect `::before`/`::after`/`::marker`/
+/// `::selection` the same way Firefox's markup view does. `::marker`'s
+/// existence is governed by the element's own `display: list-item`, not by
+/// its (non-`"none"` by default) `content`; `::before`/`::after` are
+/// suppressed exactly when their `content` computes to `none`; `::selection`
+/// is always listed.
+fn pseudo_element_node_info(node: &Node, elem: &Element, pseudo: &str) -> Option<NodeInfo> {
+    let window = window_from_node(node);
+    if pseudo == "::marker" {
+        if window.GetComputedStyle(elem, None).Display() != "list-item" {
+            return None;
+        }
+    } else if pseudo != "::selection" {
+        let style = window.GetComputedStyle(elem, Some(DOMString::from(pseudo)));
+        if style.GetPropertyValue(DOMString::from("content")) == "none" {
+            return None;
+        }
+    }
+
+    let mut info = node.summarize();
+    info.unique_id = format!("{}{}", info.unique_id, pseudo);
+    info.node_name = pseudo.to_string();
+    info.node_value = None;
+    Some(info)
+}
+
 pub fn handle_get_children(
     documents: &Documents,
     pipeline: PipelineId,
@@ -155,7 +398,7 @@ pub fn handle_get_children(
                 })
                 .collect();
 
-            let children: Vec<_> = parent
+            let mut children: Vec<_> = parent
                 .children()
                 .enumerate()
                 .filter_map(|(i, child)| {
@@ -173,6 +416,22 @@ pub fn handle_get_children(
                 })
                 .collect();
 
+            if let Some(elem) = parent.downcast::<Element>() {
+                let pseudo_children: Vec<_> = GENERATED_PSEUDO_ELEMENTS
+                    .iter()
+                    .filter_map(|pseudo| pseudo_element_node_info(&parent, elem, pseudo))
+                    .collect();
+                // `::marker`/`::before` render before a node's light-DOM
+                // children, `::after`/`::selection` after.
+                let (leading, trailing): (Vec<_>, Vec<_>) = pseudo_children
+                    .into_iter()
+                    .partition(|info| info.node_name == "::marker" || info.node_name == "::before");
+                let mut ordered = leading;
+                ordered.append(&mut children);
+                ordered.extend(trailing);
+                children = ordered;
+            }
+
             reply.send(Some(children)).unwrap();
         },
     };
@@ -255,12 +514,157 @@ pub fn handle_get_stylesheet_style(
     reply.send(msg).unwrap();
 }
 
+/// Compute the CSS specificity triple `(id, class, type)` of a selector,
+/// where a selector list (comma-separated) contributes the max specificity
+/// over its components. This follows
+/// <https://drafts.csswg.org/selectors/#specificity-rules>: the universal
+/// selector and combinators contribute nothing, attribute selectors and
+/// pseudo-classes count as classes, pseudo-elements count as types, and the
+/// functional pseudo-classes `:not()`/`:is()`/`:has()` contribute the
+/// specificity of their most specific argument rather than being counted as
+/// a class themselves (`:where()` contributes nothing at all).
+fn compute_specificity(selector_text: &str) -> Specificity {
+    split_top_level_commas(selector_text)
+        .into_iter()
+        .map(|component| compute_compound_specificity(component.trim()))
+        .max()
+        .unwrap_or_default()
+}
+
+/// Splits a selector list on its top-level commas, ignoring commas nested
+/// inside `()` or `[]` — e.g. the argument list of `:is(a, b)` or an
+/// attribute selector's value — so each returned piece is one full selector.
+fn split_top_level_commas(selector: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut depth = 0i32;
+    for (i, c) in selector.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&selector[start..i]);
+                start = i + c.len_utf8();
+            },
+            _ => {},
+        }
+    }
+    parts.push(&selector[start..]);
+    parts
+}
+
+/// Finds the index of the `)` matching the `(` at `open`, accounting for
+/// nested parentheses (e.g. `:not(:is(a, b))`).
+fn matching_close_paren(selector: &str, open: usize) -> usize {
+    let bytes = selector.as_bytes();
+    let mut depth = 0;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i;
+                }
+            },
+            _ => {},
+        }
+        i += 1;
+    }
+    selector.len().saturating_sub(1)
+}
+
+fn compute_compound_specificity(selector: &str) -> Specificity {
+    let (mut id, mut class, mut type_) = (0u32, 0u32, 0u32);
+    let bytes = selector.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] as char {
+            '#' | '.' => {
+                if bytes[i] == b'#' {
+                    id += 1;
+                } else {
+                    class += 1;
+                }
+                i += 1;
+                // The ID or class name itself is not a type selector.
+                while i < bytes.len() &&
+                    ((bytes[i] as char).is_alphanumeric() || bytes[i] == b'-' || bytes[i] == b'_')
+                {
+                    i += 1;
+                }
+            },
+            '[' => {
+                class += 1;
+                // Skip the attribute name, operator and value up to the `]`.
+                i = selector[i..].find(']').map_or(bytes.len(), |offset| i + offset + 1);
+            },
+            ':' => {
+                let is_pseudo_element = selector[i..].starts_with("::");
+                let name_start = i + if is_pseudo_element { 2 } else { 1 };
+                let name_end = selector[name_start..]
+                    .find(|c: char| !(c.is_alphanumeric() || c == '-'))
+                    .map_or(selector.len(), |offset| name_start + offset);
+                let name = &selector[name_start..name_end];
+                i = name_end;
+                if bytes.get(i) == Some(&b'(') {
+                    let close = matching_close_paren(selector, i);
+                    let args = &selector[i + 1..close];
+                    i = close + 1;
+                    match name {
+                        // `:where()` contributes nothing at all, while
+                        // `:is()`/`:not()`/`:has()` contribute the
+                        // specificity of their most specific argument
+                        // selector instead of being counted as a class.
+                        // Every other functional pseudo-class (`:nth-child()`,
+                        // `:lang()`, `:dir()`, ...) takes a non-selector
+                        // argument and just counts as one class, same as a
+                        // plain pseudo-class; a functional pseudo-element
+                        // (`::part()`, `::slotted()`) counts as one type.
+                        "where" => {},
+                        "is" | "not" | "has" => {
+                            let inner = split_top_level_commas(args)
+                                .into_iter()
+                                .map(|arg| compute_compound_specificity(arg.trim()))
+                                .max()
+                                .unwrap_or_default();
+                            id += inner.id;
+                            class += inner.class;
+                            type_ += inner.type_;
+                        },
+                        _ if is_pseudo_element => type_ += 1,
+                        _ => class += 1,
+                    }
+                } else if is_pseudo_element {
+                    type_ += 1;
+                } else {
+                    class += 1;
+                }
+            },
+            '*' | '>' | '+' | '~' | ' ' => {
+                i += 1;
+            },
+            c if c.is_alphanumeric() || c == '-' || c == '_' => {
+                type_ += 1;
+                while i < bytes.len() &&
+                    ((bytes[i] as char).is_alphanumeric() || bytes[i] == b'-' || bytes[i] == b'_')
+                {
+                    i += 1;
+                }
+            },
+            _ => i += 1,
+        }
+    }
+    Specificity { id, class, type_ }
+}
+
 #[allow(crown::unrooted_must_root)]
 pub fn handle_get_selectors(
     documents: &Documents,
     pipeline: PipelineId,
     node_id: String,
-    reply: IpcSender<Option<Vec<(String, usize)>>>,
+    reply: IpcSender<Option<Vec<SelectorInfo>>>,
 ) {
     let msg = (|| {
         let node = find_node_by_unique_id(documents, pipeline, &node_id)?;
@@ -270,17 +674,22 @@ pub fn handle_get_selectors(
         let owner = stylesheets_owner_from_node(&*node);
 
         let rules = (0..owner.stylesheet_count())
-            .filter_map(|i| {
-                let stylesheet = owner.stylesheet_at(i)?;
+            .filter_map(|stylesheet_index| {
+                let stylesheet = owner.stylesheet_at(stylesheet_index)?;
                 let list = stylesheet.GetCssRules().ok()?;
                 let elem = node.downcast::<Element>()?;
 
-                Some((0..list.Length()).filter_map(move |j| {
-                    let rule = list.Item(j)?;
+                Some((0..list.Length()).filter_map(move |rule_index| {
+                    let rule = list.Item(rule_index)?;
                     let style = rule.downcast::<CSSStyleRule>()?;
                     let selector = style.SelectorText();
                     let _ = elem.Matches(selector.clone()).ok()?.then_some(())?;
-                    Some((selector.into(), i))
+                    Some(SelectorInfo {
+                        selector: selector.to_string(),
+                        specificity: compute_specificity(&selector),
+                        stylesheet_index,
+                        rule_index: rule_index as usize,
+                    })
                 }))
             })
             .flatten()
@@ -296,6 +705,7 @@ pub fn handle_get_computed_style(
     documents: &Documents,
     pipeline: PipelineId,
     node_id: String,
+    pseudo_element: Option<String>,
     reply: IpcSender<Option<Vec<NodeStyle>>>,
 ) {
     let node = match find_node_by_unique_id(documents, pipeline, &node_id) {
@@ -307,7 +717,8 @@ pub fn handle_get_computed_style(
     let elem = node
         .downcast::<Element>()
         .expect("This should be an element");
-    let computed_style = window.GetComputedStyle(elem, None);
+    let computed_style =
+        window.GetComputedStyle(elem, pseudo_element.map(DOMString::from));
 
     let msg = (0..computed_style.Length())
         .map(|i| {
@@ -327,6 +738,7 @@ pub fn handle_get_layout(
     documents: &Documents,
     pipeline: PipelineId,
     node_id: String,
+    pseudo_element: Option<String>,
     reply: IpcSender<Option<ComputedNodeLayout>>,
 ) {
     let node = match find_node_by_unique_id(documents, pipeline, &node_id) {
@@ -345,7 +757,8 @@ pub fn handle_get_layout(
     let elem = node
         .downcast::<Element>()
         .expect("should be getting layout of element");
-    let computed_style = window.GetComputedStyle(elem, None);
+    let computed_style =
+        window.GetComputedStyle(elem, pseudo_element.map(DOMString::from));
 
     reply
         .send(Some(ComputedNodeLayout {
@@ -421,6 +834,44 @@ pub fn handle_modify_attribute(
     }
 }
 
+thread_local! {
+    /// Declarations removed by a `Disable` modification, keyed by the style
+    /// declaration they came from plus the property name, so a later
+    /// `Enable` can restore the exact value and priority that were in
+    /// effect. The script thread is single-threaded, so a thread-local cache
+    /// is sufficient and avoids round-tripping the disabled value through
+    /// the devtools client.
+    static DISABLED_DECLARATIONS: RefCell<HashMap<(String, String), (String, String)>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Resolve the [`CSSStyleDeclaration`] a modification should apply to: the
+/// element's inline style when no `target` is given, or the declaration of
+/// a matched author rule (mirroring how `handle_get_stylesheet_style`
+/// resolves a stylesheet + rule index) when one is. Returns the declaration
+/// plus a stable key identifying it, used to remember disabled properties.
+#[allow(crown::unrooted_must_root)]
+fn resolve_target_declaration(
+    node: &Node,
+    inline_style: &DomRoot<CSSStyleDeclaration>,
+    target: &Option<RuleTarget>,
+) -> Option<(DomRoot<CSSStyleDeclaration>, String)> {
+    match target {
+        None => Some((inline_style.clone(), format!("inline:{}", node.unique_id()))),
+        Some(target) => {
+            let owner = stylesheets_owner_from_node(node);
+            let stylesheet = owner.stylesheet_at(target.stylesheet_index)?;
+            let list = stylesheet.GetCssRules().ok()?;
+            let rule = list.Item(target.rule_index as u32)?;
+            let style_rule = rule.downcast::<CSSStyleRule>()?;
+            Some((
+                style_rule.Style(),
+                format!("sheet:{}:rule:{}", target.stylesheet_index, target.rule_index),
+            ))
+        },
+    }
+}
+
 pub fn handle_modify_rule(
     documents: &Documents,
     pipeline: PipelineId,
@@ -442,14 +893,102 @@ pub fn handle_modify_rule(
     let elem = node
         .downcast::<HTMLElement>()
         .expect("This should be an HTMLElement");
-    let style = elem.Style();
+    let inline_style = elem.Style();
 
     for modification in modifications {
-        let _ = style.SetProperty(
-            modification.name.into(),
-            modification.value.into(),
-            modification.priority.into(),
-        );
+        let (target, result) = match modification {
+            RuleModification::Set {
+                target,
+                name,
+                value,
+                priority,
+            } => {
+                let result = resolve_target_declaration(&node, &inline_style, &target).map(
+                    |(style, _)| {
+                        let _ = style.SetProperty(name.into(), value.into(), priority.into());
+                    },
+                );
+                (target, result)
+            },
+            RuleModification::Create {
+                target,
+                name,
+                value,
+                priority,
+            } => {
+                let result = resolve_target_declaration(&node, &inline_style, &target).map(
+                    |(style, _)| {
+                        let _ = style.SetProperty(name.into(), value.into(), priority.into());
+                    },
+                );
+                (target, result)
+            },
+            RuleModification::Remove { target, name } => {
+                let result =
+                    resolve_target_declaration(&node, &inline_style, &target).map(|(style, _)| {
+                        let _ = style.RemoveProperty(name.into());
+                    });
+                (target, result)
+            },
+            RuleModification::Disable { target, name } => {
+                let result = resolve_target_declaration(&node, &inline_style, &target).map(
+                    |(style, key)| {
+                        let cache_key = (key, name.clone());
+                        // A property that's already disabled has nothing
+                        // left in the declaration to remove; re-disabling it
+                        // must be a no-op rather than overwrite the cached
+                        // original value with the empty string it reads now.
+                        let already_disabled = DISABLED_DECLARATIONS
+                            .with(|cache| cache.borrow().contains_key(&cache_key));
+                        if already_disabled {
+                            return;
+                        }
+                        let value = style.GetPropertyValue(name.clone().into()).to_string();
+                        let priority = style.GetPropertyPriority(name.clone().into()).to_string();
+                        let _ = style.RemoveProperty(name.into());
+                        DISABLED_DECLARATIONS.with(|cache| {
+                            cache.borrow_mut().insert(cache_key, (value, priority));
+                        });
+                    },
+                );
+                (target, result)
+            },
+            RuleModification::Enable { target, name } => {
+                let result = resolve_target_declaration(&node, &inline_style, &target).map(
+                    |(style, key)| {
+                        let disabled = DISABLED_DECLARATIONS
+                            .with(|cache| cache.borrow_mut().remove(&(key, name.clone())));
+                        if let Some((value, priority)) = disabled {
+                            let _ = style.SetProperty(name.into(), value.into(), priority.into());
+                        }
+                    },
+                );
+                (target, result)
+            },
+            RuleModification::Rename {
+                target,
+                old_name,
+                new_name,
+            } => {
+                let result = resolve_target_declaration(&node, &inline_style, &target).map(
+                    |(style, _)| {
+                        let value = style.GetPropertyValue(old_name.clone().into()).to_string();
+                        let priority =
+                            style.GetPropertyPriority(old_name.clone().into()).to_string();
+                        let _ = style.RemoveProperty(old_name.into());
+                        let _ = style.SetProperty(new_name.into(), value.into(), priority.into());
+                    },
+                );
+                (target, result)
+            },
+        };
+
+        if result.is_none() {
+            warn!(
+                "could not resolve style declaration for rule modification (target: {:?})",
+                target
+            );
+        }
     }
 }
 
@@ -491,20 +1030,155 @@ pub fn handle_reload(documents: &Documents, id: PipelineId) {
     }
 }
 
+/// Keyword sets shared by groups of longhands, keyed by every property name
+/// that accepts them. The style system's generated longhand modules don't
+/// expose a single trait for listing a property's keyword strings at
+/// runtime (each has its own `SpecifiedValue` enum type), so this table is
+/// the practical stand-in: it's still data describing every property that
+/// shares a keyword set, rather than one hardcoded arm per property, and is
+/// meant to grow as more keyword-valued properties are added.
+fn keyword_value_table() -> &'static [(&'static [&'static str], &'static [&'static str])] {
+    &[
+        (
+            &["display"],
+            &[
+                "none", "block", "inline", "inline-block", "flex", "inline-flex", "grid",
+                "inline-grid", "table", "list-item", "contents",
+            ],
+        ),
+        (&["position"], &["static", "relative", "absolute", "fixed", "sticky"]),
+        (&["box-sizing"], &["content-box", "border-box"]),
+        (&["float"], &["none", "left", "right", "inline-start", "inline-end"]),
+        (&["clear"], &["none", "left", "right", "both", "inline-start", "inline-end"]),
+        (
+            &["overflow", "overflow-x", "overflow-y"],
+            &["visible", "hidden", "scroll", "auto", "clip"],
+        ),
+        (
+            &["text-align"],
+            &["start", "end", "left", "right", "center", "justify"],
+        ),
+        (&["visibility"], &["visible", "hidden", "collapse"]),
+        (
+            &["white-space"],
+            &["normal", "nowrap", "pre", "pre-wrap", "pre-line", "break-spaces"],
+        ),
+        (
+            &["flex-direction"],
+            &["row", "row-reverse", "column", "column-reverse"],
+        ),
+        (&["flex-wrap"], &["nowrap", "wrap", "wrap-reverse"]),
+        (
+            &["justify-content"],
+            &["flex-start", "flex-end", "center", "space-between", "space-around", "space-evenly"],
+        ),
+        (
+            &["align-items", "align-content", "align-self"],
+            &["stretch", "flex-start", "flex-end", "center", "baseline"],
+        ),
+    ]
+}
+
+/// Keyword values accepted by a longhand, used to seed the inspector's
+/// autocomplete. Properties not covered in [`keyword_value_table`] still get
+/// the CSS-wide keywords.
+fn keyword_values_for(property: &str) -> Vec<String> {
+    let mut values: Vec<String> = keyword_value_table()
+        .iter()
+        .find(|(properties, _)| properties.contains(&property))
+        .map(|(_, values)| values.iter().map(|v| v.to_string()).collect())
+        .unwrap_or_default();
+    // CSS-wide keywords are accepted by every property.
+    for keyword in ["initial", "inherit", "unset", "revert"] {
+        values.push(keyword.to_string());
+    }
+    values
+}
+
+/// Value types and functions `@supports` can test a property against, beyond
+/// its keyword set (e.g. `@supports (width: calc(1px + 1%))`), keyed by
+/// every property name pattern that accepts them.
+fn supports_value_table() -> &'static [(&'static [&'static str], &'static [&'static str])] {
+    &[
+        (
+            &[
+                "margin", "padding", "width", "height", "top", "right", "bottom", "left",
+                "inset", "gap", "row-gap", "column-gap", "font-size", "border-width",
+                "border-radius", "outline-width", "letter-spacing", "line-height",
+                "text-indent",
+            ],
+            &["<length>", "<percentage>", "calc()"],
+        ),
+        (
+            &[
+                "color", "background-color", "border-color", "outline-color",
+                "text-decoration-color", "caret-color",
+            ],
+            &["<color>", "rgb()", "hsl()", "var()"],
+        ),
+        (
+            &["transform"],
+            &["translate()", "rotate()", "scale()", "matrix()"],
+        ),
+        (
+            &["animation-duration", "transition-duration", "animation-delay", "transition-delay"],
+            &["<time>"],
+        ),
+        (
+            &["animation-timing-function", "transition-timing-function"],
+            &["cubic-bezier()", "steps()", "linear()"],
+        ),
+        (&["grid-template-columns", "grid-template-rows"], &["repeat()", "minmax()", "fr"]),
+        (&["z-index"], &["<integer>"]),
+        (&["opacity"], &["<number>"]),
+    ]
+}
+
+fn supports_values_for(property: &str) -> Vec<String> {
+    supports_value_table()
+        .iter()
+        .find(|(properties, _)| {
+            properties
+                .iter()
+                .any(|prefix| property == *prefix || property.starts_with(&format!("{prefix}-")))
+        })
+        .map(|(_, values)| values.iter().map(|v| v.to_string()).collect())
+        .unwrap_or_default()
+}
+
 pub fn handle_get_css_database(reply: IpcSender<HashMap<String, CssDatabaseProperty>>) {
-    let database: HashMap<_, _> = ENABLED_LONGHAND_PROPERTIES
+    let mut database: HashMap<String, CssDatabaseProperty> = ENABLED_LONGHAND_PROPERTIES
         .iter()
-        .map(|l| {
+        .map(|longhand| {
+            let name = longhand.name().to_string();
             (
-                l.name().into(),
+                name.clone(),
                 CssDatabaseProperty {
-                    is_inherited: l.inherited(),
-                    values: vec![], // TODO: Get allowed values for each property
-                    supports: vec![],
-                    subproperties: vec![l.name().into()],
+                    is_inherited: longhand.inherited(),
+                    values: keyword_values_for(&name),
+                    supports: supports_values_for(&name),
+                    subproperties: vec![name],
                 },
             )
         })
         .collect();
+
+    for shorthand in ENABLED_SHORTHAND_PROPERTIES.iter() {
+        let name = shorthand.name().to_string();
+        let subproperties: Vec<String> = shorthand
+            .longhands()
+            .map(|longhand| longhand.name().to_string())
+            .collect();
+        database.insert(
+            name,
+            CssDatabaseProperty {
+                is_inherited: false,
+                values: vec![],
+                supports: vec![],
+                subproperties,
+            },
+        );
+    }
+
     let _ = reply.send(database);
 }
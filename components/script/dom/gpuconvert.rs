@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::num::NonZeroU64;
 
 use webgpu::wgc::binding_model::{BindGroupEntry, BindingResource, BufferBinding};
@@ -19,9 +20,10 @@ use crate::dom::bindings::codegen::Bindings::WebGPUBinding::{
     GPUBlendComponent, GPUBlendFactor, GPUBlendOperation, GPUBufferBindingType, GPUColor,
     GPUCompareFunction, GPUCullMode, GPUExtent3D, GPUFilterMode, GPUFrontFace, GPUImageCopyBuffer,
     GPUImageCopyTexture, GPUImageDataLayout, GPUIndexFormat, GPULoadOp, GPUObjectDescriptorBase,
-    GPUOrigin3D, GPUPrimitiveState, GPUPrimitiveTopology, GPUSamplerBindingType,
-    GPUStencilOperation, GPUStorageTextureAccess, GPUStoreOp, GPUTextureAspect, GPUTextureFormat,
-    GPUTextureSampleType, GPUTextureViewDimension, GPUVertexFormat,
+    GPUOrigin3D, GPUPrimitiveState, GPUPrimitiveTopology, GPUQuerySetDescriptor, GPUQueryType,
+    GPUSamplerBindingType, GPUStencilOperation, GPUStorageTextureAccess, GPUStoreOp,
+    GPUTextureAspect, GPUTextureFormat, GPUTextureSampleType, GPUTextureViewDimension,
+    GPUVertexFormat,
 };
 use crate::dom::bindings::error::Fallible;
 use crate::dom::types::GPUDevice;
@@ -36,6 +38,8 @@ impl From<GPUTextureFormat> for wgt::TextureFormat {
             GPUTextureFormat::R16uint => wgt::TextureFormat::R16Uint,
             GPUTextureFormat::R16sint => wgt::TextureFormat::R16Sint,
             GPUTextureFormat::R16float => wgt::TextureFormat::R16Float,
+            GPUTextureFormat::R16unorm => wgt::TextureFormat::R16Unorm,
+            GPUTextureFormat::R16snorm => wgt::TextureFormat::R16Snorm,
             GPUTextureFormat::Rg8unorm => wgt::TextureFormat::Rg8Unorm,
             GPUTextureFormat::Rg8snorm => wgt::TextureFormat::Rg8Snorm,
             GPUTextureFormat::Rg8uint => wgt::TextureFormat::Rg8Uint,
@@ -46,6 +50,8 @@ impl From<GPUTextureFormat> for wgt::TextureFormat {
             GPUTextureFormat::Rg16uint => wgt::TextureFormat::Rg16Uint,
             GPUTextureFormat::Rg16sint => wgt::TextureFormat::Rg16Sint,
             GPUTextureFormat::Rg16float => wgt::TextureFormat::Rg16Float,
+            GPUTextureFormat::Rg16unorm => wgt::TextureFormat::Rg16Unorm,
+            GPUTextureFormat::Rg16snorm => wgt::TextureFormat::Rg16Snorm,
             GPUTextureFormat::Rgba8unorm => wgt::TextureFormat::Rgba8Unorm,
             GPUTextureFormat::Rgba8unorm_srgb => wgt::TextureFormat::Rgba8UnormSrgb,
             GPUTextureFormat::Rgba8snorm => wgt::TextureFormat::Rgba8Snorm,
@@ -212,6 +218,283 @@ impl From<GPUTextureFormat> for wgt::TextureFormat {
     }
 }
 
+/// Maps a [`GPUTextureFormat`] to its `wgt` equivalent, raising a validation
+/// error if the format requires a [`GPUDevice`] feature the device wasn't
+/// created with — e.g. any `Bc*` format needs `texture-compression-bc`, any
+/// `Astc*` format needs `texture-compression-astc`. Every call site that
+/// hands a script-author-supplied format to wgpu (texture/view creation,
+/// render/compute pipeline targets, copy descriptors) should validate
+/// through this rather than the unconditional [`From`] impl above, which
+/// has no way to reject an unsupported format.
+///
+/// <https://gpuweb.github.io/gpuweb/#abstract-opdef-validating-gputextureformat>
+pub fn validate_texture_format(
+    format: GPUTextureFormat,
+    device: &GPUDevice,
+) -> Fallible<wgt::TextureFormat> {
+    let required_feature = match format {
+        GPUTextureFormat::Bc1_rgba_unorm |
+        GPUTextureFormat::Bc1_rgba_unorm_srgb |
+        GPUTextureFormat::Bc2_rgba_unorm |
+        GPUTextureFormat::Bc2_rgba_unorm_srgb |
+        GPUTextureFormat::Bc3_rgba_unorm |
+        GPUTextureFormat::Bc3_rgba_unorm_srgb |
+        GPUTextureFormat::Bc4_r_unorm |
+        GPUTextureFormat::Bc4_r_snorm |
+        GPUTextureFormat::Bc5_rg_unorm |
+        GPUTextureFormat::Bc5_rg_snorm |
+        GPUTextureFormat::Bc6h_rgb_ufloat |
+        GPUTextureFormat::Bc6h_rgb_float |
+        GPUTextureFormat::Bc7_rgba_unorm |
+        GPUTextureFormat::Bc7_rgba_unorm_srgb => Some(wgt::Features::TEXTURE_COMPRESSION_BC),
+        GPUTextureFormat::Etc2_rgb8unorm |
+        GPUTextureFormat::Etc2_rgb8unorm_srgb |
+        GPUTextureFormat::Etc2_rgb8a1unorm |
+        GPUTextureFormat::Etc2_rgb8a1unorm_srgb |
+        GPUTextureFormat::Etc2_rgba8unorm |
+        GPUTextureFormat::Etc2_rgba8unorm_srgb |
+        GPUTextureFormat::Eac_r11unorm |
+        GPUTextureFormat::Eac_r11snorm |
+        GPUTextureFormat::Eac_rg11unorm |
+        GPUTextureFormat::Eac_rg11snorm => Some(wgt::Features::TEXTURE_COMPRESSION_ETC2),
+        GPUTextureFormat::Astc_4x4_unorm |
+        GPUTextureFormat::Astc_4x4_unorm_srgb |
+        GPUTextureFormat::Astc_5x4_unorm |
+        GPUTextureFormat::Astc_5x4_unorm_srgb |
+        GPUTextureFormat::Astc_5x5_unorm |
+        GPUTextureFormat::Astc_5x5_unorm_srgb |
+        GPUTextureFormat::Astc_6x5_unorm |
+        GPUTextureFormat::Astc_6x5_unorm_srgb |
+        GPUTextureFormat::Astc_6x6_unorm |
+        GPUTextureFormat::Astc_6x6_unorm_srgb |
+        GPUTextureFormat::Astc_8x5_unorm |
+        GPUTextureFormat::Astc_8x5_unorm_srgb |
+        GPUTextureFormat::Astc_8x6_unorm |
+        GPUTextureFormat::Astc_8x6_unorm_srgb |
+        GPUTextureFormat::Astc_8x8_unorm |
+        GPUTextureFormat::Astc_8x8_unorm_srgb |
+        GPUTextureFormat::Astc_10x5_unorm |
+        GPUTextureFormat::Astc_10x5_unorm_srgb |
+        GPUTextureFormat::Astc_10x6_unorm |
+        GPUTextureFormat::Astc_10x6_unorm_srgb |
+        GPUTextureFormat::Astc_10x8_unorm |
+        GPUTextureFormat::Astc_10x8_unorm_srgb |
+        GPUTextureFormat::Astc_10x10_unorm |
+        GPUTextureFormat::Astc_10x10_unorm_srgb |
+        GPUTextureFormat::Astc_12x10_unorm |
+        GPUTextureFormat::Astc_12x10_unorm_srgb |
+        GPUTextureFormat::Astc_12x12_unorm |
+        GPUTextureFormat::Astc_12x12_unorm_srgb => Some(wgt::Features::TEXTURE_COMPRESSION_ASTC),
+        GPUTextureFormat::Depth32float_stencil8 => Some(wgt::Features::DEPTH32FLOAT_STENCIL8),
+        GPUTextureFormat::R16unorm |
+        GPUTextureFormat::R16snorm |
+        GPUTextureFormat::Rg16unorm |
+        GPUTextureFormat::Rg16snorm => Some(wgt::Features::TEXTURE_FORMAT_16BIT_NORM),
+        _ => None,
+    };
+    if let Some(feature) = required_feature {
+        if !device.features().contains(feature) {
+            return Err(Error::Type(format!(
+                "{:?} requires the '{:?}' device feature, which wasn't enabled when the device was requested",
+                format, feature,
+            )));
+        }
+    }
+    Ok(wgt::TextureFormat::from(format))
+}
+
+/// Block-copy metadata for a `wgt::TextureFormat`, analogous to the format
+/// tables wgpu-hal keeps alongside its own backend conversions: how many
+/// texels a compressed block covers, and how many bytes one block occupies.
+/// Uncompressed formats report a 1x1 "block".
+///
+/// <https://gpuweb.github.io/gpuweb/#texel-block-size>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureFormatInfo {
+    pub block_width: u32,
+    pub block_height: u32,
+    pub block_size: u32,
+}
+
+pub fn format_info(format: wgt::TextureFormat) -> TextureFormatInfo {
+    let uncompressed = |block_size| TextureFormatInfo {
+        block_width: 1,
+        block_height: 1,
+        block_size,
+    };
+    let bc_or_etc2 = |block_size| TextureFormatInfo {
+        block_width: 4,
+        block_height: 4,
+        block_size,
+    };
+    match format {
+        wgt::TextureFormat::R8Unorm |
+        wgt::TextureFormat::R8Snorm |
+        wgt::TextureFormat::R8Uint |
+        wgt::TextureFormat::R8Sint |
+        wgt::TextureFormat::Stencil8 => uncompressed(1),
+        wgt::TextureFormat::R16Uint |
+        wgt::TextureFormat::R16Sint |
+        wgt::TextureFormat::R16Float |
+        wgt::TextureFormat::R16Unorm |
+        wgt::TextureFormat::R16Snorm |
+        wgt::TextureFormat::Rg8Unorm |
+        wgt::TextureFormat::Rg8Snorm |
+        wgt::TextureFormat::Rg8Uint |
+        wgt::TextureFormat::Rg8Sint |
+        wgt::TextureFormat::Depth16Unorm => uncompressed(2),
+        wgt::TextureFormat::R32Uint |
+        wgt::TextureFormat::R32Sint |
+        wgt::TextureFormat::R32Float |
+        wgt::TextureFormat::Rg16Uint |
+        wgt::TextureFormat::Rg16Sint |
+        wgt::TextureFormat::Rg16Float |
+        wgt::TextureFormat::Rg16Unorm |
+        wgt::TextureFormat::Rg16Snorm |
+        wgt::TextureFormat::Rgba8Unorm |
+        wgt::TextureFormat::Rgba8UnormSrgb |
+        wgt::TextureFormat::Rgba8Snorm |
+        wgt::TextureFormat::Rgba8Uint |
+        wgt::TextureFormat::Rgba8Sint |
+        wgt::TextureFormat::Bgra8Unorm |
+        wgt::TextureFormat::Bgra8UnormSrgb |
+        wgt::TextureFormat::Rgb10a2Unorm |
+        wgt::TextureFormat::Rgb10a2Uint |
+        wgt::TextureFormat::Rg11b10Ufloat |
+        wgt::TextureFormat::Rgb9e5Ufloat |
+        wgt::TextureFormat::Depth32Float |
+        wgt::TextureFormat::Depth24Plus |
+        wgt::TextureFormat::Depth24PlusStencil8 => uncompressed(4),
+        wgt::TextureFormat::Depth32FloatStencil8 => uncompressed(5),
+        wgt::TextureFormat::Rg32Uint |
+        wgt::TextureFormat::Rg32Sint |
+        wgt::TextureFormat::Rg32Float |
+        wgt::TextureFormat::Rgba16Uint |
+        wgt::TextureFormat::Rgba16Sint |
+        wgt::TextureFormat::Rgba16Float => uncompressed(8),
+        wgt::TextureFormat::Rgba32Uint |
+        wgt::TextureFormat::Rgba32Sint |
+        wgt::TextureFormat::Rgba32Float => uncompressed(16),
+        wgt::TextureFormat::Bc1RgbaUnorm |
+        wgt::TextureFormat::Bc1RgbaUnormSrgb |
+        wgt::TextureFormat::Bc4RUnorm |
+        wgt::TextureFormat::Bc4RSnorm => bc_or_etc2(8),
+        wgt::TextureFormat::Bc2RgbaUnorm |
+        wgt::TextureFormat::Bc2RgbaUnormSrgb |
+        wgt::TextureFormat::Bc3RgbaUnorm |
+        wgt::TextureFormat::Bc3RgbaUnormSrgb |
+        wgt::TextureFormat::Bc5RgUnorm |
+        wgt::TextureFormat::Bc5RgSnorm |
+        wgt::TextureFormat::Bc6hRgbUfloat |
+        wgt::TextureFormat::Bc6hRgbFloat |
+        wgt::TextureFormat::Bc7RgbaUnorm |
+        wgt::TextureFormat::Bc7RgbaUnormSrgb => bc_or_etc2(16),
+        wgt::TextureFormat::Etc2Rgb8Unorm |
+        wgt::TextureFormat::Etc2Rgb8UnormSrgb |
+        wgt::TextureFormat::Etc2Rgb8A1Unorm |
+        wgt::TextureFormat::Etc2Rgb8A1UnormSrgb |
+        wgt::TextureFormat::EacR11Unorm |
+        wgt::TextureFormat::EacR11Snorm => bc_or_etc2(8),
+        wgt::TextureFormat::Etc2Rgba8Unorm |
+        wgt::TextureFormat::Etc2Rgba8UnormSrgb |
+        wgt::TextureFormat::EacRg11Unorm |
+        wgt::TextureFormat::EacRg11Snorm => bc_or_etc2(16),
+        wgt::TextureFormat::Astc { block, .. } => {
+            let (block_width, block_height) = match block {
+                AstcBlock::B4x4 => (4, 4),
+                AstcBlock::B5x4 => (5, 4),
+                AstcBlock::B5x5 => (5, 5),
+                AstcBlock::B6x5 => (6, 5),
+                AstcBlock::B6x6 => (6, 6),
+                AstcBlock::B8x5 => (8, 5),
+                AstcBlock::B8x6 => (8, 6),
+                AstcBlock::B8x8 => (8, 8),
+                AstcBlock::B10x5 => (10, 5),
+                AstcBlock::B10x6 => (10, 6),
+                AstcBlock::B10x8 => (10, 8),
+                AstcBlock::B10x10 => (10, 10),
+                AstcBlock::B12x10 => (12, 10),
+                AstcBlock::B12x12 => (12, 12),
+            };
+            TextureFormatInfo {
+                block_width,
+                block_height,
+                block_size: 16,
+            }
+        },
+        _ => uncompressed(4),
+    }
+}
+
+/// Validates the spec's "validate GPUImageDataLayout" steps against the
+/// format being copied to/from: `bytesPerRow` (when present) must be a
+/// multiple of the format's bytes-per-block.
+///
+/// <https://gpuweb.github.io/gpuweb/#abstract-opdef-validating-gpuimagedatalayout>
+pub fn validate_image_data_layout(
+    layout: &GPUImageDataLayout,
+    format: wgt::TextureFormat,
+) -> Fallible<()> {
+    let info = format_info(format);
+    if let Some(bytes_per_row) = layout.bytesPerRow {
+        if bytes_per_row % info.block_size != 0 {
+            return Err(Error::Type(format!(
+                "bytesPerRow ({bytes_per_row}) must be a multiple of {format:?}'s block size ({})",
+                info.block_size
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Validates the spec's "validate GPUImageCopyTexture" steps against the
+/// format being copied to/from: the origin must land on a block boundary.
+///
+/// <https://gpuweb.github.io/gpuweb/#abstract-opdef-validating-gputexelcopytextureinfo>
+pub fn validate_image_copy_texture_origin(
+    origin: &wgt::Origin3d,
+    format: wgt::TextureFormat,
+) -> Fallible<()> {
+    let info = format_info(format);
+    if origin.x % info.block_width != 0 || origin.y % info.block_height != 0 {
+        return Err(Error::Type(format!(
+            "copy origin ({}, {}) must be aligned to {format:?}'s {}x{} block size",
+            origin.x, origin.y, info.block_width, info.block_height
+        )));
+    }
+    Ok(())
+}
+
+/// Validates the spec's "validate texture copy range" extent check: the
+/// copy's width and height must each be a multiple of the destination
+/// format's block size, unless the copy reaches the edge of the mip level
+/// being copied (a subresource's final row/column of blocks may be partial).
+///
+/// <https://gpuweb.github.io/gpuweb/#abstract-opdef-validating-gputexelcopytextureinfo>
+pub fn validate_image_copy_size(
+    copy_size: &wgt::Extent3d,
+    origin: &wgt::Origin3d,
+    mip_level_size: wgt::Extent3d,
+    format: wgt::TextureFormat,
+) -> Fallible<()> {
+    let info = format_info(format);
+    let reaches_right_edge = origin.x + copy_size.width == mip_level_size.width;
+    let reaches_bottom_edge = origin.y + copy_size.height == mip_level_size.height;
+    if !reaches_right_edge && copy_size.width % info.block_width != 0 {
+        return Err(Error::Type(format!(
+            "copy width ({}) must be a multiple of {format:?}'s block width ({}) unless it reaches the subresource's edge",
+            copy_size.width, info.block_width
+        )));
+    }
+    if !reaches_bottom_edge && copy_size.height % info.block_height != 0 {
+        return Err(Error::Type(format!(
+            "copy height ({}) must be a multiple of {format:?}'s block height ({}) unless it reaches the subresource's edge",
+            copy_size.height, info.block_height
+        )));
+    }
+    Ok(())
+}
+
 impl TryFrom<&GPUExtent3D> for wgt::Extent3d {
     type Error = Error;
 
@@ -259,8 +542,8 @@ impl From<GPUVertexFormat> for wgt::VertexFormat {
             GPUVertexFormat::Sint8x4 => wgt::VertexFormat::Sint8x4,
             GPUVertexFormat::Unorm8x2 => wgt::VertexFormat::Unorm8x2,
             GPUVertexFormat::Unorm8x4 => wgt::VertexFormat::Unorm8x4,
-            GPUVertexFormat::Snorm8x2 => wgt::VertexFormat::Unorm8x2,
-            GPUVertexFormat::Snorm8x4 => wgt::VertexFormat::Unorm8x4,
+            GPUVertexFormat::Snorm8x2 => wgt::VertexFormat::Snorm8x2,
+            GPUVertexFormat::Snorm8x4 => wgt::VertexFormat::Snorm8x4,
             GPUVertexFormat::Uint16x2 => wgt::VertexFormat::Uint16x2,
             GPUVertexFormat::Uint16x4 => wgt::VertexFormat::Uint16x4,
             GPUVertexFormat::Sint16x2 => wgt::VertexFormat::Sint16x2,
@@ -283,6 +566,7 @@ impl From<GPUVertexFormat> for wgt::VertexFormat {
             GPUVertexFormat::Sint32x2 => wgt::VertexFormat::Sint32x2,
             GPUVertexFormat::Sint32x3 => wgt::VertexFormat::Sint32x3,
             GPUVertexFormat::Sint32x4 => wgt::VertexFormat::Sint32x4,
+            GPUVertexFormat::Unorm10_10_10_2 => wgt::VertexFormat::Unorm10_10_10_2,
         }
     }
 }
@@ -407,14 +691,30 @@ impl From<&GPUBlendComponent> for wgt::BlendComponent {
     }
 }
 
-pub fn convert_load_op(op: Option<GPULoadOp>) -> wgpu_com::LoadOp {
+/// Builds the wgpu load-op for one attachment, carrying along the clear
+/// value the author specified (`clearValue` for color, `depthClearValue` /
+/// `stencilClearValue` for depth/stencil) rather than discarding it. Per the
+/// WebGPU spec, a clear value is required whenever `loadOp` is `"clear"`.
+///
+/// <https://gpuweb.github.io/gpuweb/#abstract-opdef-validating-gpurenderpasscoloroattachment>
+pub fn convert_load_op<T>(op: Option<GPULoadOp>, clear_value: Option<T>) -> Fallible<wgpu_com::LoadOp<T>> {
     match op {
-        Some(GPULoadOp::Load) => wgpu_com::LoadOp::Load,
-        Some(GPULoadOp::Clear) => wgpu_com::LoadOp::Clear,
-        None => wgpu_com::LoadOp::Clear,
+        Some(GPULoadOp::Load) => Ok(wgpu_com::LoadOp::Load),
+        Some(GPULoadOp::Clear) | None => {
+            let clear_value = clear_value.ok_or_else(|| {
+                Error::Type("clearValue is required when loadOp is \"clear\"".to_string())
+            })?;
+            Ok(wgpu_com::LoadOp::Clear(clear_value))
+        },
     }
 }
 
+/// Converts a color attachment's optional `clearValue` to `wgt::Color`,
+/// propagating the `GPUColor` sequence-length validation error if present.
+pub fn convert_color_clear_value(clear_value: Option<&GPUColor>) -> Fallible<Option<wgt::Color>> {
+    clear_value.map(wgt::Color::try_from).transpose()
+}
+
 pub fn convert_store_op(op: Option<GPUStoreOp>) -> wgpu_com::StoreOp {
     match op {
         Some(GPUStoreOp::Store) => wgpu_com::StoreOp::Store,
@@ -497,6 +797,22 @@ impl TryFrom<&GPUImageCopyTexture> for wgpu_com::ImageCopyTexture {
     }
 }
 
+/// Converts a [`GPUImageCopyTexture`] like the plain [`TryFrom`] impl above,
+/// additionally validating its origin and the copy's extent against the
+/// copied texture's format (block alignment) before handing the result to
+/// wgpu.
+pub fn validate_and_convert_image_copy_texture(
+    ic_texture: &GPUImageCopyTexture,
+    copy_size: &wgt::Extent3d,
+    mip_level_size: wgt::Extent3d,
+    format: wgt::TextureFormat,
+) -> Fallible<wgpu_com::ImageCopyTexture> {
+    let converted = wgpu_com::ImageCopyTexture::try_from(ic_texture)?;
+    validate_image_copy_texture_origin(&converted.origin, format)?;
+    validate_image_copy_size(copy_size, &converted.origin, mip_level_size, format)?;
+    Ok(converted)
+}
+
 impl<'a> Into<Option<Cow<'a, str>>> for &GPUObjectDescriptorBase {
     fn into(self) -> Option<Cow<'a, str>> {
         if self.label.is_empty() {
@@ -506,6 +822,15 @@ impl<'a> Into<Option<Cow<'a, str>>> for &GPUObjectDescriptorBase {
         }
     }
 }
+/// The outer `Fallible` covers content-timeline argument errors (thrown
+/// synchronously); the inner `Result`'s `webgpu::Error` is a device-timeline
+/// validation error, returned to the caller so it can be reported against
+/// whichever `GPUDevice` operation this entry is part of. Routing that error
+/// through the spec's per-device `pushErrorScope`/`popErrorScope` stack and
+/// `uncapturederror` event is `GPUDevice`-level plumbing this tree doesn't
+/// have yet (there's no `GPUDevice` error-scope stack to hook into here);
+/// this function only produces the error value for that future call site to
+/// consume.
 pub fn convert_bind_group_layout_entry(
     bgle: &GPUBindGroupLayoutEntry,
     device: &GPUDevice,
@@ -513,7 +838,8 @@ pub fn convert_bind_group_layout_entry(
     let number_of_provided_bindings = bgle.buffer.is_some() as u8 +
         bgle.sampler.is_some() as u8 +
         bgle.storageTexture.is_some() as u8 +
-        bgle.texture.is_some() as u8;
+        bgle.texture.is_some() as u8 +
+        bgle.externalTexture.is_some() as u8;
     let ty = if let Some(buffer) = &bgle.buffer {
         Some(wgt::BindingType::Buffer {
             ty: match buffer.type_ {
@@ -541,7 +867,7 @@ pub fn convert_bind_group_layout_entry(
                 GPUStorageTextureAccess::Read_only => wgt::StorageTextureAccess::ReadOnly,
                 GPUStorageTextureAccess::Read_write => wgt::StorageTextureAccess::ReadWrite,
             },
-            format: device.validate_texture_format_required_features(&storage.format)?,
+            format: validate_texture_format(storage.format, device)?,
             view_dimension: storage.viewDimension.into(),
         })
     } else if let Some(texture) = &bgle.texture {
@@ -558,6 +884,8 @@ pub fn convert_bind_group_layout_entry(
             view_dimension: texture.viewDimension.into(),
             multisampled: texture.multisampled,
         })
+    } else if bgle.externalTexture.is_some() {
+        Some(wgt::BindingType::ExternalTexture)
     } else {
         assert_eq!(number_of_provided_bindings, 0);
         None
@@ -570,7 +898,7 @@ pub fn convert_bind_group_layout_entry(
         ty
     }
     .ok_or(webgpu::Error::Validation(
-        "Exactly on entry type must be provided".to_string(),
+        "Exactly one entry type must be provided".to_string(),
     ));
 
     Ok(ty.map(|ty| wgt::BindGroupLayoutEntry {
@@ -581,6 +909,52 @@ pub fn convert_bind_group_layout_entry(
     }))
 }
 
+/// Validates that `ty` doesn't require a device feature the device wasn't
+/// created with, analogous to how [`validate_texture_format`] gates
+/// compressed/extended texture formats behind their own features.
+///
+/// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-createqueryset>
+fn validate_query_type_required_features(ty: GPUQueryType, device: &GPUDevice) -> Fallible<()> {
+    let required_feature = match ty {
+        GPUQueryType::Timestamp => Some(wgt::Features::TIMESTAMP_QUERY),
+        GPUQueryType::Occlusion => None,
+    };
+    if let Some(feature) = required_feature {
+        if !device.features().contains(feature) {
+            return Err(Error::Type(format!(
+                "{:?} requires the '{:?}' device feature, which wasn't enabled when the device was requested",
+                ty, feature,
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Maps a [`GPUQuerySetDescriptor`] to its `wgt` equivalent, gating
+/// `GPUQueryType::Timestamp` behind the device's `timestamp-query` feature
+/// via [`validate_query_type_required_features`].
+///
+/// The `resolveQuerySet` resolve path and the `occlusionQuerySet`/
+/// `timestampWrites` fields of the render/compute pass descriptors are wired
+/// where those pass encoders are converted, not here.
+///
+/// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-createqueryset>
+pub fn convert_query_set_descriptor(
+    descriptor: &GPUQuerySetDescriptor,
+    device: &GPUDevice,
+) -> Fallible<wgt::QuerySetDescriptor<Option<Cow<'static, str>>>> {
+    validate_query_type_required_features(descriptor.type_, device)?;
+    let ty = match descriptor.type_ {
+        GPUQueryType::Occlusion => wgt::QueryType::Occlusion,
+        GPUQueryType::Timestamp => wgt::QueryType::Timestamp,
+    };
+    Ok(wgt::QuerySetDescriptor {
+        label: (&descriptor.parent).into(),
+        ty,
+        count: descriptor.count,
+    })
+}
+
 impl TryFrom<&GPUColor> for wgt::Color {
     type Error = Error;
 
@@ -609,24 +983,46 @@ impl TryFrom<&GPUColor> for wgt::Color {
     }
 }
 
-impl<'a> From<&GPUProgrammableStage> for ProgrammableStageDescriptor<'a> {
-    fn from(stage: &GPUProgrammableStage) -> Self {
-        Self {
-            module: stage.module.id().0,
-            entry_point: stage
-                .entryPoint
-                .as_ref()
-                .map(|ep| Cow::Owned(ep.to_string())),
-            constants: Cow::Owned(
-                stage
-                    .constants
-                    .as_ref()
-                    .map(|records| records.iter().map(|(k, v)| (k.0.clone(), **v)).collect())
-                    .unwrap_or_default(),
-            ),
-            zero_initialize_workgroup_memory: true,
+/// Converts a [`GPUProgrammableStage`] to its `wgt-core` equivalent.
+///
+/// Unlike the old infallible conversion this replaces, `zero_initialize_workgroup_memory`
+/// is taken from the device/pipeline option that requested it (defaulting to
+/// `true`, the safe behavior shader authors already get today) instead of
+/// being hardcoded, and `stage.constants` is validated before it reaches
+/// wgpu-core: a duplicate key is a content-author mistake we can catch here
+/// rather than silently letting the later entry win, and an identifier that
+/// doesn't match one of the shader module's declared overridable constants
+/// is surfaced as a validation error instead of being forwarded.
+pub fn convert_programmable_stage<'a>(
+    stage: &'a GPUProgrammableStage,
+    zero_initialize_workgroup_memory: bool,
+    declared_constant_identifiers: &HashSet<String>,
+) -> Fallible<ProgrammableStageDescriptor<'a>> {
+    let mut constants = HashMap::new();
+    if let Some(records) = stage.constants.as_ref() {
+        for (key, value) in records.iter() {
+            let key = key.0.clone();
+            if !declared_constant_identifiers.contains(&key) {
+                return Err(Error::Type(format!(
+                    "'{key}' is not a declared overridable constant identifier of this shader module",
+                )));
+            }
+            if constants.insert(key.clone(), **value).is_some() {
+                return Err(Error::Type(format!(
+                    "duplicate key '{key}' in pipeline-overridable constants",
+                )));
+            }
         }
     }
+    Ok(ProgrammableStageDescriptor {
+        module: stage.module.id().0,
+        entry_point: stage
+            .entryPoint
+            .as_ref()
+            .map(|ep| Cow::Owned(ep.to_string())),
+        constants: Cow::Owned(constants),
+        zero_initialize_workgroup_memory,
+    })
 }
 
 impl From<&GPUBindGroupEntry> for BindGroupEntry<'_> {
@@ -643,6 +1039,9 @@ impl From<&GPUBindGroupEntry> for BindGroupEntry<'_> {
                         size: b.size.and_then(wgt::BufferSize::new),
                     })
                 },
+                GPUBindingResource::GPUExternalTexture(ref t) => {
+                    BindingResource::ExternalTexture(t.id().0)
+                },
             },
         }
     }